@@ -1,5 +1,4 @@
-use tokio::runtime::Runtime;
-use reqwest::Client;
+use termion::event::Key;
 use clipboard::{
     ClipboardContext,
     ClipboardProvider,
@@ -11,9 +10,33 @@ use crate::{
     ui::views::{
         ViewType,
         View,
-        ContentType,
     },
-    invidious,
+    invidious::Invidious,
+    events::Event,
+    fetch::{
+        Fetcher,
+        Fetched,
+    },
+    input::{
+        LineEditor,
+        Suggestions,
+        InputMode,
+        MAX_SUGGESTIONS,
+    },
+    history::SearchHistory,
+    subscriptions::Subscriptions,
+    playlists::LocalPlaylists,
+    watch_history::WatchHistory,
+    bookmarks::Bookmarks,
+    queue::{
+        Queue,
+        QueueEntry,
+        SavedQueue,
+        SavedQueues,
+        ShuffleMode,
+    },
+    auth::Credentials,
+    data::SearchFilters,
     utils,
 };
 use std::{
@@ -21,30 +44,47 @@ use std::{
         HashMap,
         VecDeque,
     },
-    sync::{
-        Arc,
-        RwLock,
+    sync::mpsc::Sender,
+    fmt::Display,
+    time::{
+        Duration,
+        Instant,
     },
 };
 
+/// How often the queues are saved while running.
+const QUEUE_SAVE_INTERVAL: Duration = Duration::from_secs(10);
+
+pub struct Notification {
+    pub message: String,
+    pub is_error: bool,
+    pub created: Instant,
+}
+
 pub struct App {
     pub quit: bool,
     pub config: Config,
     pub loaded_data: LoadedData,
    
     pub cmdline_focused: bool,
-    pub input: String,
+    pub input: LineEditor,
+    pub suggestions: Suggestions,
+    pub search_history: SearchHistory,
+    pub notification: Option<Notification>,
     
-    pub client: Client,
-    pub runtime: Arc<RwLock<Runtime>>,
+    pub api: Invidious,
+    pub fetcher: Fetcher,
+    pub tick: usize,
     
     pub player: Player,
-    pub current_audio: String,
-    pub next_audio: String,
-    pub current_video: String,
-    pub next_video: String,
-    pub video_queue: VecDeque<(String, String, Option<String>)>,
-    pub audio_queue: VecDeque<(String, String, Option<String>)>,
+    pub video_queue: Queue,
+    pub audio_queue: Queue,
+    pub autoplay: bool,
+    pub autoplay_source: Option<String>,
+    pub autoplay_history: VecDeque<String>,
+    /// Queues of the previous session that haven't been restored yet.
+    pub saved_queues: Option<SavedQueues>,
+    queues_saved: (Instant, SavedQueues),
     
     pub focused_view: ViewType,
     pub view_list: HashMap<ViewType, View>,
@@ -53,21 +93,34 @@ pub struct App {
 }
 
 impl App {
-    pub fn new(config: Config) -> Self {
+    pub fn new(config: Config, tx: Sender<Event<Key>>) -> Self {
+        let api = Invidious::new(config.instance_list(), Duration::from_secs(config.request_timeout));
+        api.set_credentials(Credentials::load());
         App {
             cmdline_focused: false,
-            input: String::new(),
-            current_audio: String::from("None\n"),
-            next_audio: String::from("None\n"),
-            current_video: String::from("None\n"),
-            next_video: String::from("None\n"),
-            loaded_data: LoadedData::default(),
-            client: Client::new(),
-            runtime: Arc::new(RwLock::new(Runtime::new().unwrap())),
-            player: Player::default(),
+            input: LineEditor::default(),
+            suggestions: Suggestions::default(),
+            search_history: SearchHistory::load(config.search_history_size),
+            notification: None,
+            loaded_data: LoadedData {
+                subscriptions: Subscriptions::load(),
+                playlists: LocalPlaylists::load(),
+                watch_history: WatchHistory::load(config.watch_history),
+                bookmarks: Bookmarks::load(),
+                ..LoadedData::default()
+            },
+            api,
+            fetcher: Fetcher::new(tx),
+            tick: 0,
+            player: Player::new(&config.quality),
             quit: false,
-            audio_queue: VecDeque::new(),
-            video_queue: VecDeque::new(),
+            audio_queue: Queue::default(),
+            video_queue: Queue::default(),
+            autoplay: config.autoplay,
+            autoplay_source: None,
+            autoplay_history: VecDeque::new(),
+            saved_queues: None,
+            queues_saved: (Instant::now(), SavedQueues::default()),
              
             focused_view: ViewType::Home,
             view_list: HashMap::new(),
@@ -76,27 +129,281 @@ impl App {
         }
     }
 
+    /// Checks which instances of `proxy_streams` actually proxy streams,
+    /// leaving playback to youtube-dl until they answer.
+    fn check_proxies(&mut self) {
+        for instance in self.config.proxy_streams.iter() {
+            let instance = instance.trim_end_matches('/').to_owned();
+            let api = self.api.clone();
+            self.fetcher.spawn(async move {
+                let result = api.check_proxy(&instance).await;
+                Fetched::ProxyCheck(instance, result)
+            });
+        }
+    }
+
     pub fn run_setup(&mut self) {
-        let client = &self.client;
-        let (trending, popular) = self.runtime.write().unwrap().block_on(invidious::load_home(&client)).unwrap();
+        if let Some(view) = self.view_list.get_mut(&ViewType::Home) {
+            if let Some(window) = view.root_windows.get_mut(0) {
+                window.loading = true;
+            }
+            if let Some(window) = view.root_windows.get_mut(1) {
+                window.loading = true;
+            }
+        }
 
-        let trending_text = utils::video_to_text(trending.clone());
-        let popular_text = utils::video_to_text(popular.clone());
-        //let top_text = search::video_to_text(top.clone());
+        let api = self.api.clone();
+        self.fetcher.spawn(async move {
+            Fetched::Home(api.load_home().await)
+        });
 
-        if let Some(view) = self.view_list.get_mut(&ViewType::Home) {
-            if let Some(mut window) = view.root_windows.get_mut(0) {
-                window.content = ContentType::MediaContent(Arc::new(RwLock::new(trending_text)));
-                window.selected = 0;
+        utils::update_account_tabs(self);
+        utils::load_account(self);
+        self.check_proxies();
+
+        let saved = SavedQueues::load();
+        if !saved.is_empty() {
+            let len = saved.len();
+            self.saved_queues = Some(saved);
+            if self.config.restore_queue {
+                self.restore_queues();
             }
-            if let Some(mut window) = view.root_windows.get_mut(1) {
-                window.content = ContentType::MediaContent(Arc::new(RwLock::new(popular_text)));
-                window.selected = 0;
+            else {
+                self.notify_info(format!("Run :queue restore to restore the {} queued tracks of the last session", len));
             }
         }
-        self.loaded_data.trending_videos = trending;
-        self.loaded_data.popular_videos = popular;
-        //self.loaded_data.top_videos = top;
+    }
+
+    /// Loads the queues of the previous session back into the players.
+    pub fn restore_queues(&mut self) {
+        let saved = match self.saved_queues.take() {
+            Some(saved) => saved,
+            None => {
+                self.notify_error("No saved queue to restore");
+                return
+            },
+        };
+        let len = saved.len();
+        self.restore_queue(saved.audio, false);
+        self.restore_queue(saved.video, true);
+        self.notify_info(format!("Restored {} queued tracks", len));
+    }
+
+    fn restore_queue(&mut self, saved: SavedQueue, is_video: bool) {
+        if saved.entries.is_empty() {
+            return
+        }
+        let urls = saved.entries
+            .iter()
+            .map(|entry| self.api.watch_url(&entry.video_id))
+            .collect();
+        let current = saved.current.unwrap_or(0).min(saved.entries.len() - 1);
+        self.player.load_playlist(is_video, urls, current, saved.position);
+        let queue = self.queue_mut(is_video);
+        queue.replace(saved.entries);
+        queue.current = Some(current);
+        self.record_playing(is_video);
+    }
+
+    /// Saves both queues every few seconds, so they survive a crash, or right
+    /// away when `now` is set. Saved queues that haven't been restored are
+    /// kept until something new is queued.
+    pub fn save_queues(&mut self, now: bool) {
+        if !now && self.queues_saved.0.elapsed() < QUEUE_SAVE_INTERVAL {
+            return
+        }
+        self.queues_saved.0 = Instant::now();
+
+        let queues = SavedQueues {
+            audio: self.audio_queue.snapshot(self.player.playback_time().unwrap_or(0)),
+            video: self.video_queue.snapshot(self.player.video_time()),
+        };
+        if queues == self.queues_saved.1 || (queues.is_empty() && self.saved_queues.is_some()) {
+            return
+        }
+        self.saved_queues = None;
+        match queues.save() {
+            Ok(_) => self.queues_saved.1 = queues,
+            Err(e) => log::error!("Unable to save queues: {}", e),
+        }
+    }
+
+    /// Fetches the related videos of the current track once the audio queue
+    /// has run dry, so `Fetched::Autoplay` can queue the next one.
+    pub fn autoplay_related(&mut self) {
+        if !self.autoplay || self.audio_queue.has_next() {
+            return
+        }
+
+        let video_id = match self.player.audio_path().and_then(|path| utils::video_id_from_url(&path)) {
+            Some(video_id) => video_id,
+            None => return,
+        };
+        if self.autoplay_source.as_ref() == Some(&video_id) {
+            return
+        }
+
+        self.autoplay_source = Some(video_id.clone());
+        let api = self.api.clone();
+        self.fetcher.spawn(async move {
+            let result = api.video(&video_id).await;
+            Fetched::Autoplay(video_id, result)
+        });
+    }
+
+    pub fn queue_mut(&mut self, is_video: bool) -> &mut Queue {
+        if is_video {
+            &mut self.video_queue
+        }
+        else {
+            &mut self.audio_queue
+        }
+    }
+
+    /// Follows mpv to the entry of each queue that is playing.
+    pub fn sync_queues(&mut self) {
+        let playing = (self.playing_id(false), self.playing_id(true));
+
+        let audio_pos = self.player.audio_playlist_pos();
+        let looped = self.audio_queue.set_position(audio_pos);
+        if looped && self.player.shuffle == ShuffleMode::EachLoop {
+            let moves = self.audio_queue.shuffle();
+            self.move_audio_tracks(moves);
+        }
+        let video_pos = self.player.video_playlist_pos();
+        self.video_queue.set_position(video_pos);
+
+        // Every video that starts is recorded, however it got there.
+        if self.playing_id(false) != playing.0 {
+            self.record_playing(false);
+        }
+        if self.playing_id(true) != playing.1 {
+            self.record_playing(true);
+        }
+    }
+
+    fn playing_id(&self, is_video: bool) -> Option<String> {
+        let queue = if is_video { &self.video_queue } else { &self.audio_queue };
+        queue.current_entry().map(|entry| entry.video_id.clone())
+    }
+
+    fn record_playing(&mut self, is_video: bool) {
+        let video = self.queue_mut(is_video).current_entry().map(QueueEntry::to_video);
+        if let Some(video) = video {
+            self.loaded_data.watch_history.record(&video);
+        }
+    }
+
+    /// Switches the audio queue to the next shuffle mode. Turning shuffle on
+    /// shuffles the queue and turning it off restores the queued order.
+    pub fn cycle_shuffle(&mut self) {
+        let mode = self.player.shuffle.next();
+        self.player.shuffle = mode;
+        match mode {
+            ShuffleMode::Once => {
+                let moves = self.audio_queue.shuffle();
+                self.move_audio_tracks(moves);
+                self.notify_info("Shuffled the audio queue");
+            },
+            ShuffleMode::EachLoop => self.notify_info("Shuffling the audio queue on each loop"),
+            ShuffleMode::Off => {
+                let moves = self.audio_queue.unshuffle();
+                self.move_audio_tracks(moves);
+                self.notify_info("Restored the order of the audio queue");
+            },
+        }
+    }
+
+    fn move_audio_tracks(&mut self, moves: Vec<(usize, usize)>) {
+        for (from, to) in moves {
+            self.player.playlist_move(false, from, to);
+        }
+    }
+
+    /// Remembers how far the tracks in the audio and video players got,
+    /// saving the watch history every few seconds.
+    pub fn track_watch_position(&mut self) {
+        if !self.loaded_data.watch_history.recording {
+            return
+        }
+        let video_id = self.player.audio_path().and_then(|path| utils::video_id_from_url(&path));
+        if let (Some(video_id), Some(position)) = (video_id, self.player.playback_time()) {
+            self.loaded_data.watch_history.update_position(&video_id, position);
+        }
+        if let Some(video_id) = self.playing_id(true) {
+            self.loaded_data.watch_history.update_position(&video_id, self.player.video_time());
+        }
+        if self.loaded_data.watch_history.save_due() {
+            if let Err(e) = self.loaded_data.watch_history.save() {
+                log::error!("Unable to save watch history: {}", e);
+            }
+        }
+    }
+
+    /// Requests suggestions for the search being typed once it has stopped
+    /// changing for `SUGGESTION_DELAY`.
+    pub fn request_suggestions(&mut self) {
+        if !self.cmdline_focused || !self.suggestions.is_due() {
+            return
+        }
+        self.suggestions.changed = None;
+        if self.input.mode == InputMode::Command {
+            self.suggest_playlists();
+            return
+        }
+
+        let query = match SearchFilters::parse(self.input.text()) {
+            Ok((query, _)) => query,
+            Err(_) => return,
+        };
+        if query.is_empty() {
+            self.suggestions.clear();
+            return
+        }
+        if query == self.suggestions.requested {
+            return
+        }
+
+        self.suggestions.requested = query.clone();
+        let api = self.api.clone();
+        self.fetcher.spawn(async move {
+            let result = api.search_suggestions(&query).await;
+            Fetched::Suggestions(query, result)
+        });
+    }
+
+    /// Completes local playlist names after `playlist add`.
+    pub fn suggest_playlists(&mut self) {
+        let mut words = self.input.text().splitn(3, ' ');
+        self.suggestions.items = match (words.next(), words.next(), words.next()) {
+            (Some("playlist"), Some("add"), Some(prefix)) => {
+                self.loaded_data.playlists
+                    .names_matching(prefix)
+                    .into_iter()
+                    .take(MAX_SUGGESTIONS)
+                    .map(|name| format!("playlist add {}", name))
+                    .collect()
+            },
+            _ => vec![],
+        };
+    }
+
+    pub fn notify_info<T: Display>(&mut self, message: T) {
+        log::info!("{}", message);
+        self.notification = Some(Notification {
+            message: message.to_string(),
+            is_error: false,
+            created: Instant::now(),
+        });
+    }
+
+    pub fn notify_error<T: Display>(&mut self, message: T) {
+        log::error!("{}", message);
+        self.notification = Some(Notification {
+            message: message.to_string(),
+            is_error: true,
+            created: Instant::now(),
+        });
     }
 
     pub fn view(mut self, view_type: ViewType, view: View) -> Self {
@@ -0,0 +1,35 @@
+use serde::{
+    Serialize,
+    Deserialize,
+};
+use failure::Error;
+use crate::store;
+
+const CREDENTIALS_FILE: &str = "credentials.json";
+const SCOPES: &str = ":feed,:subscriptions*,:playlists*";
+
+/// Token for an Invidious account, valid only on the instance that issued it.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Credentials {
+    pub instance: String,
+    pub token: String,
+}
+
+impl Credentials {
+    pub fn load() -> Option<Credentials> {
+        store::load(CREDENTIALS_FILE)
+    }
+
+    pub fn save(&self) -> Result<(), Error> {
+        store::save_private(CREDENTIALS_FILE, &Some(self))
+    }
+
+    pub fn remove() -> Result<(), Error> {
+        store::remove(CREDENTIALS_FILE)
+    }
+}
+
+/// Page of the instance that hands out a token for the scopes used here.
+pub fn authorize_url(instance: &str) -> String {
+    format!("{}/authorize_token?scopes={}", instance, SCOPES)
+}
@@ -0,0 +1,128 @@
+use serde::{
+    Serialize,
+    Deserialize,
+};
+use failure::Error;
+use chrono::{
+    Local,
+    TimeZone,
+};
+use crate::{
+    store,
+    media::{
+        video::Video,
+        playlist::Playlist,
+        channel::Channel,
+    },
+};
+
+const BOOKMARKS_FILE: &str = "bookmarks.json";
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Bookmark<T> {
+    pub item: T,
+    pub saved_at: i64,
+}
+
+impl<T> Bookmark<T> {
+    fn saved(&self) -> String {
+        Local.timestamp_opt(self.saved_at, 0)
+            .single()
+            .map(|date| date.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_default()
+    }
+}
+
+/// A selection that can be bookmarked.
+#[derive(Clone, Debug)]
+pub enum Bookmarked {
+    Video(Video),
+    Playlist(Playlist),
+    Channel(Channel),
+}
+
+impl Bookmarked {
+    pub fn title(&self) -> &str {
+        match self {
+            Bookmarked::Video(video) => &video.title,
+            Bookmarked::Playlist(playlist) => &playlist.title,
+            Bookmarked::Channel(channel) => &channel.author,
+        }
+    }
+}
+
+/// Videos, playlists and channels saved for later, newest first.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Bookmarks {
+    pub videos: Vec<Bookmark<Video>>,
+    pub playlists: Vec<Bookmark<Playlist>>,
+    pub channels: Vec<Bookmark<Channel>>,
+}
+
+impl Bookmarks {
+    pub fn load() -> Self {
+        store::load(BOOKMARKS_FILE)
+    }
+
+    pub fn save(&self) -> Result<(), Error> {
+        store::save(BOOKMARKS_FILE, self)
+    }
+
+    /// Bookmarks the selection, or removes it if already bookmarked. Returns
+    /// whether it is bookmarked afterwards.
+    pub fn toggle(&mut self, bookmarked: Bookmarked) -> bool {
+        match bookmarked {
+            Bookmarked::Video(video) => toggle(&mut self.videos, video, |a, b| a.video_id == b.video_id),
+            Bookmarked::Playlist(playlist) => toggle(&mut self.playlists, playlist, |a, b| a.playlist_id == b.playlist_id),
+            Bookmarked::Channel(channel) => toggle(&mut self.channels, channel, |a, b| a.author_id == b.author_id),
+        }
+    }
+
+    pub fn videos_to_text(&self) -> Vec<Vec<String>> {
+        self.videos
+            .iter()
+            .map(|bookmark| vec![bookmark.item.title.clone(), bookmark.item.author.clone(), bookmark.saved()])
+            .collect()
+    }
+
+    pub fn playlists_to_text(&self) -> Vec<Vec<String>> {
+        self.playlists
+            .iter()
+            .map(|bookmark| vec![bookmark.item.title.clone(), bookmark.item.author.clone(), bookmark.saved()])
+            .collect()
+    }
+
+    pub fn channels_to_text(&self) -> Vec<Vec<String>> {
+        self.channels
+            .iter()
+            .map(|bookmark| vec![bookmark.item.author.clone(), bookmark.saved()])
+            .collect()
+    }
+}
+
+fn toggle<T, F>(bookmarks: &mut Vec<Bookmark<T>>, item: T, same: F) -> bool
+where
+    F: Fn(&T, &T) -> bool,
+{
+    if let Some(index) = bookmarks.iter().position(|bookmark| same(&bookmark.item, &item)) {
+        bookmarks.remove(index);
+        return false
+    }
+    bookmarks.insert(0, Bookmark {
+        item,
+        saved_at: chrono::Utc::now().timestamp(),
+    });
+    true
+}
+
+impl Default for Bookmarks {
+    fn default() -> Self {
+        Bookmarks {
+            videos: vec![],
+            playlists: vec![],
+            channels: vec![],
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use failure::Error;
+use std::env;
+use log::LevelFilter;
+
+#[derive(Debug, Default)]
+pub struct Args {
+    pub instance: Option<String>,
+    pub import: Option<String>,
+    pub log_file: Option<String>,
+    pub log_level: Option<LevelFilter>,
+}
+
+impl Args {
+    pub fn parse() -> Result<Args, Error> {
+        let mut args = Args::default();
+        let mut argv = env::args().skip(1);
+
+        while let Some(arg) = argv.next() {
+            let (flag, value) = match arg.find('=') {
+                Some(i) => (arg[..i].to_owned(), Some(arg[i + 1..].to_owned())),
+                None => (arg.clone(), None),
+            };
+
+            match flag.as_str() {
+                "--instance" => {
+                    let value = value.or_else(|| argv.next());
+                    match value {
+                        Some(instance) => args.instance = Some(instance),
+                        None => return Err(failure::format_err!("--instance requires a URL")),
+                    }
+                },
+                "--import" => {
+                    let value = value.or_else(|| argv.next());
+                    match value {
+                        Some(path) => args.import = Some(path),
+                        None => return Err(failure::format_err!("--import requires a path")),
+                    }
+                },
+                "--log-file" => {
+                    let value = value.or_else(|| argv.next());
+                    match value {
+                        Some(path) => args.log_file = Some(path),
+                        None => return Err(failure::format_err!("--log-file requires a path")),
+                    }
+                },
+                "--log-level" => {
+                    let value = value.or_else(|| argv.next());
+                    match value.and_then(|level| level.parse().ok()) {
+                        Some(level) => args.log_level = Some(level),
+                        None => return Err(failure::format_err!("--log-level requires one of off, error, warn, info, debug or trace")),
+                    }
+                },
+                _ => return Err(failure::format_err!("Unknown argument: {}", arg)),
+            }
+        }
+        Ok(args)
+    }
+}
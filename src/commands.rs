@@ -0,0 +1,208 @@
+use clipboard::ClipboardProvider;
+use crate::{
+    app::App,
+    auth::{
+        self,
+        Credentials,
+    },
+    subscriptions::Subscriptions,
+    player::{
+        MAX_VOLUME,
+        MIN_SPEED,
+        MAX_SPEED,
+    },
+    import,
+    utils,
+};
+use std::fmt::Display;
+
+/// Runs a line entered after the command key, e.g. `:history clear`.
+pub fn run(app: &mut App, line: &str) {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    match words.as_slice() {
+        [] => {},
+        ["history", "clear"] => {
+            app.search_history.clear();
+            match app.search_history.save() {
+                Ok(_) => app.notify_info("Cleared search history"),
+                Err(e) => app.notify_error(format!("Unable to clear search history: {}", e)),
+            }
+        },
+        ["login"] => {
+            let url = auth::authorize_url(&app.api.instance());
+            let _ = app.clipboard.set_contents(url.clone());
+            app.notify_info(format!("Open {} (copied to clipboard), then run :login <token>", url));
+        },
+        ["login", _, ..] => login(app, argument(line, 1)),
+        ["logout"] => logout(app),
+        ["playlist", "add", _, ..] => add_to_playlist(app, argument(line, 2)),
+        ["playlist", "new", _, ..] => {
+            let name = argument(line, 2).to_owned();
+            match app.loaded_data.playlists.create(&name) {
+                Ok(_) => save_playlists(app, format!("Created playlist {}", name)),
+                Err(e) => app.notify_error(e),
+            }
+        },
+        ["playlist", "rename", _, ..] => rename_playlist(app, argument(line, 2)),
+        ["watched", "clear"] => {
+            app.loaded_data.watch_history.clear();
+            match app.loaded_data.watch_history.save() {
+                Ok(_) => app.notify_info("Cleared watch history"),
+                Err(e) => app.notify_error(format!("Unable to clear watch history: {}", e)),
+            }
+            utils::update_history_view(app);
+        },
+        ["watched", "off"] => {
+            app.loaded_data.watch_history.recording = false;
+            app.notify_info("Watch history recording paused for this session");
+        },
+        ["watched", "on"] => {
+            app.loaded_data.watch_history.recording = true;
+            app.notify_info("Watch history recording resumed");
+        },
+        ["queue", "restore"] => app.restore_queues(),
+        ["volume", volume] => {
+            match volume.trim_end_matches('%').parse::<i64>() {
+                Ok(volume) if (0..=MAX_VOLUME).contains(&volume) => app.player.set_volume(volume),
+                _ => app.notify_error(format!("Volume must be between 0 and {}", MAX_VOLUME)),
+            }
+        },
+        ["mute"] => app.player.toggle_mute(),
+        ["speed", speed] => {
+            match speed.trim_end_matches('x').parse::<f64>() {
+                Ok(speed) if (MIN_SPEED..=MAX_SPEED).contains(&speed) => app.player.set_speed(speed),
+                _ => app.notify_error(format!("Speed must be between {} and {}", MIN_SPEED, MAX_SPEED)),
+            }
+        },
+        ["import", _, ..] => import_subscriptions(app, argument(line, 1)),
+        ["export", format, _, ..] => export_subscriptions(app, format, argument(line, 2)),
+        _ => app.notify_error(format!("Unknown command: {}", line.trim())),
+    }
+}
+
+fn add_to_playlist(app: &mut App, name: &str) {
+    let video = utils::focused_window(app).and_then(|window| utils::get_video(window, &app.loaded_data));
+    let video = match video {
+        Some(video) => video,
+        None => {
+            app.notify_error("Selection is not a video");
+            return
+        },
+    };
+    let title = video.title.clone();
+    match app.loaded_data.playlists.add(name, video) {
+        Ok(true) => save_playlists(app, format!("Added {} to {}", title, name)),
+        Ok(false) => app.notify_info(format!("{} is already in {}", title, name)),
+        Err(e) => app.notify_error(e),
+    }
+}
+
+/// Renames the selected playlist, or the opened one when editing it.
+fn rename_playlist(app: &mut App, new_name: &str) {
+    let playlist = utils::focused_window(app).and_then(|window| utils::get_local_playlist(window, &app.loaded_data));
+    let name = match playlist {
+        Some(playlist) => playlist.name,
+        None => {
+            app.notify_error("Select a playlist in the playlists view to rename it");
+            return
+        },
+    };
+    if let Err(e) = app.loaded_data.playlists.rename(&name, new_name) {
+        app.notify_error(e);
+        return
+    }
+    if app.loaded_data.opened_playlist.as_ref() == Some(&name) {
+        app.loaded_data.opened_playlist = Some(new_name.to_owned());
+    }
+    save_playlists(app, format!("Renamed {} to {}", name, new_name));
+}
+
+/// Saves local playlists after an edit and refreshes the playlists view.
+pub fn save_playlists<T: Display>(app: &mut App, message: T) {
+    utils::update_playlists_view(app);
+    match app.loaded_data.playlists.save() {
+        Ok(_) => app.notify_info(message),
+        Err(e) => app.notify_error(format!("Unable to save playlists: {}", e)),
+    }
+}
+
+fn login(app: &mut App, token: &str) {
+    let credentials = Credentials {
+        instance: app.api.instance(),
+        token: token.to_owned(),
+    };
+    if let Err(e) = credentials.save() {
+        app.notify_error(format!("Unable to save credentials: {}", e));
+        return
+    }
+    app.notify_info(format!("Logged in to {}", credentials.instance));
+    app.api.set_credentials(Some(credentials));
+    utils::update_account_tabs(app);
+    utils::load_account(app);
+}
+
+fn logout(app: &mut App) {
+    if let Err(e) = Credentials::remove() {
+        app.notify_error(format!("Unable to remove credentials: {}", e));
+        return
+    }
+    app.api.set_credentials(None);
+    app.loaded_data.account_feed.clear();
+    app.loaded_data.account_playlists.clear();
+    app.loaded_data.subscriptions = Subscriptions::load();
+    utils::update_account_tabs(app);
+    utils::update_subscriptions_view(app);
+    app.notify_info("Logged out");
+}
+
+fn import_subscriptions(app: &mut App, path: &str) {
+    if app.api.is_logged_in() {
+        app.notify_error("Importing only works with local subscriptions, run :logout first");
+        return
+    }
+    let channels = match import::read_subscriptions(&import::expand_path(path)) {
+        Ok(channels) => channels,
+        Err(e) => {
+            app.notify_error(format!("Unable to import {}: {}", path, e));
+            return
+        },
+    };
+    let (imported, skipped) = app.loaded_data.subscriptions.merge(channels);
+    if let Err(e) = app.loaded_data.subscriptions.save() {
+        app.notify_error(format!("Unable to save subscriptions: {}", e));
+        return
+    }
+    utils::update_subscriptions_view(app);
+    app.notify_info(format!("Imported {} channels, skipped {} already subscribed", imported, skipped));
+}
+
+fn export_subscriptions(app: &mut App, format: &str, path: &str) {
+    let channels = &app.loaded_data.subscriptions.channels;
+    let path_buf = import::expand_path(path);
+    let result = match format {
+        "opml" => import::write_opml(&path_buf, channels),
+        "newpipe" => import::write_newpipe(&path_buf, channels),
+        _ => {
+            app.notify_error(format!("Unknown export format: {}, expected opml or newpipe", format));
+            return
+        },
+    };
+    match result {
+        Ok(_) => {
+            let count = channels.len();
+            app.notify_info(format!("Exported {} channels to {}", count, path));
+        },
+        Err(e) => app.notify_error(format!("Unable to export to {}: {}", path, e)),
+    }
+}
+
+/// The rest of the line after the first `skip` words, so paths may contain
+/// spaces.
+fn argument(line: &str, skip: usize) -> &str {
+    let mut rest = line.trim_start();
+    for _ in 0..skip {
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        rest = rest[end..].trim_start();
+    }
+    rest.trim_end()
+}
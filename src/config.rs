@@ -28,6 +28,35 @@ pub struct Keybinds {
     pub seek_audio_backward: Key,
     pub queue_view: Key,
     pub loop_playlist_audio: Key,
+    pub toggle_comment_sort: Key,
+    pub toggle_autoplay: Key,
+    pub command: Key,
+    pub subscribe: Key,
+    pub subscriptions_view: Key,
+    pub add_to_playlist: Key,
+    pub playlists_view: Key,
+    pub history_view: Key,
+    pub bookmarks_view: Key,
+    pub move_video_down: Key,
+    pub move_video_up: Key,
+    pub remove_from_playlist: Key,
+    pub rename_playlist: Key,
+    pub delete_playlist: Key,
+    pub bookmark: Key,
+    pub clear_queue: Key,
+    pub shuffle: Key,
+    pub volume_up: Key,
+    pub volume_down: Key,
+    pub toggle_mute: Key,
+    pub speed_up: Key,
+    pub speed_down: Key,
+    pub reset_speed: Key,
+    pub switch_player: Key,
+    pub next_track: Key,
+    pub prev_track: Key,
+    pub stop: Key,
+    pub toggle_fullscreen: Key,
+    pub pick_format: Key,
 }
 
 impl Default for Keybinds {
@@ -44,8 +73,13 @@ impl Default for Keybinds {
             home_view: Key::F(1),
             search_view: Key::F(2),
             queue_view: Key::F(3),
+            subscriptions_view: Key::F(4),
+            playlists_view: Key::F(5),
+            history_view: Key::F(6),
+            bookmarks_view: Key::F(7),
             
             search: Key::Char('/'),
+            command: Key::Char(':'),
             
             play_pause: Key::Char(' '),
             
@@ -58,9 +92,35 @@ impl Default for Keybinds {
             
             open_selection: Key::Char('o'),
             copy_url: Key::Char('y'),
+            subscribe: Key::Char('s'),
             submit_entry: Key::Char('\n'),
             
             queue_video: Key::Char('v'),
+            toggle_comment_sort: Key::Char('t'),
+            toggle_autoplay: Key::Char('R'),
+
+            add_to_playlist: Key::Char('p'),
+            move_video_down: Key::Char('J'),
+            move_video_up: Key::Char('K'),
+            remove_from_playlist: Key::Char('d'),
+            rename_playlist: Key::Char('r'),
+            delete_playlist: Key::Char('D'),
+            bookmark: Key::Char('b'),
+            clear_queue: Key::Char('C'),
+            shuffle: Key::Char('x'),
+
+            volume_up: Key::Char('0'),
+            volume_down: Key::Char('9'),
+            toggle_mute: Key::Char('m'),
+            speed_up: Key::Char(']'),
+            speed_down: Key::Char('['),
+            reset_speed: Key::Backspace,
+            switch_player: Key::Char('\t'),
+            next_track: Key::Char('>'),
+            prev_track: Key::Char('<'),
+            stop: Key::Char('S'),
+            toggle_fullscreen: Key::Char('f'),
+            pick_format: Key::Char('Q'),
         }
     }
 }
@@ -90,6 +150,35 @@ pub struct KeybindsAsStr {
     pub seek_audio_backward: String,
     pub queue_view: String,
     pub loop_playlist_audio: String,
+    pub toggle_comment_sort: String,
+    pub toggle_autoplay: String,
+    pub command: String,
+    pub subscribe: String,
+    pub subscriptions_view: String,
+    pub add_to_playlist: String,
+    pub playlists_view: String,
+    pub history_view: String,
+    pub bookmarks_view: String,
+    pub move_video_down: String,
+    pub move_video_up: String,
+    pub remove_from_playlist: String,
+    pub rename_playlist: String,
+    pub delete_playlist: String,
+    pub bookmark: String,
+    pub clear_queue: String,
+    pub shuffle: String,
+    pub volume_up: String,
+    pub volume_down: String,
+    pub toggle_mute: String,
+    pub speed_up: String,
+    pub speed_down: String,
+    pub reset_speed: String,
+    pub switch_player: String,
+    pub next_track: String,
+    pub prev_track: String,
+    pub stop: String,
+    pub toggle_fullscreen: String,
+    pub pick_format: String,
 }
 
 impl Default for KeybindsAsStr {
@@ -104,11 +193,16 @@ impl Default for KeybindsAsStr {
             back: String::from("esc"),
             
             search: String::from("/"),
+            command: String::from(":"),
             play_pause: String::from(" "),
            
             home_view: String::from("f1"),
             search_view: String::from("f2"),
             queue_view: String::from("f3"),
+            subscriptions_view: String::from("f4"),
+            playlists_view: String::from("f5"),
+            history_view: String::from("f6"),
+            bookmarks_view: String::from("f7"),
             
             audio_only: String::from("a"),
             queue_audio: String::from("A"),
@@ -118,17 +212,57 @@ impl Default for KeybindsAsStr {
             open_selection: String::from("o"),
             submit_entry: String::from("enter"),
             copy_url: String::from("y"),
+            subscribe: String::from("s"),
             seek_audio_forward: String::from("right"),
             seek_audio_backward: String::from("left"),
             
             queue_video: String::from("v"),
+            toggle_comment_sort: String::from("t"),
+            toggle_autoplay: String::from("R"),
+
+            add_to_playlist: String::from("p"),
+            move_video_down: String::from("J"),
+            move_video_up: String::from("K"),
+            remove_from_playlist: String::from("d"),
+            rename_playlist: String::from("r"),
+            delete_playlist: String::from("D"),
+            bookmark: String::from("b"),
+            clear_queue: String::from("C"),
+            shuffle: String::from("x"),
+            volume_up: String::from("0"),
+            volume_down: String::from("9"),
+            toggle_mute: String::from("m"),
+            speed_up: String::from("]"),
+            speed_down: String::from("["),
+            reset_speed: String::from("backspace"),
+            switch_player: String::from("tab"),
+            next_track: String::from(">"),
+            prev_track: String::from("<"),
+            stop: String::from("S"),
+            toggle_fullscreen: String::from("f"),
+            pick_format: String::from("Q"),
         }
     }
 }
 
+pub const DEFAULT_INSTANCE: &str = "https://yewtu.be";
+pub const DEFAULT_REQUEST_TIMEOUT: u64 = 10;
+pub const DEFAULT_SEARCH_HISTORY_SIZE: usize = 500;
+pub const DEFAULT_RESOLUTION: u32 = 720;
+
 #[derive(Debug, Deserialize)]
+#[serde(default)]
 pub struct Config {
     pub keybinds: KeybindsAsStr,
+    pub instance: Option<String>,
+    pub instances: Vec<String>,
+    pub request_timeout: u64,
+    pub autoplay: bool,
+    pub search_history_size: usize,
+    pub watch_history: bool,
+    pub restore_queue: bool,
+    pub quality: Quality,
+    pub proxy_streams: Vec<String>,
 
     #[serde(skip_deserializing)]
     pub keys: Keybinds,
@@ -148,6 +282,23 @@ impl Config {
         }
     }
 
+    /// Instances in the order they should be tried, with `instance` (or
+    /// `--instance`) taking priority over the `instances` list.
+    pub fn instance_list(&self) -> Vec<String> {
+        let mut list: Vec<String> = vec![];
+        for instance in self.instance.iter().chain(self.instances.iter()) {
+            let instance = instance.trim_end_matches('/').to_owned();
+            if !instance.is_empty() && !list.contains(&instance) {
+                list.push(instance);
+            }
+        }
+
+        if list.is_empty() {
+            list.push(String::from(DEFAULT_INSTANCE));
+        }
+        list
+    }
+
     fn de_keybinds(keybinds_as_str: &mut KeybindsAsStr) -> Keybinds {
         Keybinds {
             quit: Self::str_to_key(&mut keybinds_as_str.quit),
@@ -160,6 +311,9 @@ impl Config {
             back: Self::str_to_key(&mut keybinds_as_str.back),
             open_selection: Self::str_to_key(&mut keybinds_as_str.open_selection),
             search: Self::str_to_key(&mut keybinds_as_str.search),
+            command: Self::str_to_key(&mut keybinds_as_str.command),
+            subscribe: Self::str_to_key(&mut keybinds_as_str.subscribe),
+            subscriptions_view: Self::str_to_key(&mut keybinds_as_str.subscriptions_view),
             play_pause: Self::str_to_key(&mut keybinds_as_str.play_pause),
             audio_only: Self::str_to_key(&mut keybinds_as_str.audio_only),
             queue_video: Self::str_to_key(&mut keybinds_as_str.queue_video),
@@ -172,6 +326,32 @@ impl Config {
             seek_audio_forward: Self::str_to_key(&mut keybinds_as_str.seek_audio_forward),
             seek_audio_backward: Self::str_to_key(&mut keybinds_as_str.seek_audio_backward),
             queue_view: Self::str_to_key(&mut keybinds_as_str.queue_view),
+            toggle_comment_sort: Self::str_to_key(&mut keybinds_as_str.toggle_comment_sort),
+            toggle_autoplay: Self::str_to_key(&mut keybinds_as_str.toggle_autoplay),
+            add_to_playlist: Self::str_to_key(&mut keybinds_as_str.add_to_playlist),
+            playlists_view: Self::str_to_key(&mut keybinds_as_str.playlists_view),
+            history_view: Self::str_to_key(&mut keybinds_as_str.history_view),
+            bookmarks_view: Self::str_to_key(&mut keybinds_as_str.bookmarks_view),
+            move_video_down: Self::str_to_key(&mut keybinds_as_str.move_video_down),
+            move_video_up: Self::str_to_key(&mut keybinds_as_str.move_video_up),
+            remove_from_playlist: Self::str_to_key(&mut keybinds_as_str.remove_from_playlist),
+            rename_playlist: Self::str_to_key(&mut keybinds_as_str.rename_playlist),
+            delete_playlist: Self::str_to_key(&mut keybinds_as_str.delete_playlist),
+            bookmark: Self::str_to_key(&mut keybinds_as_str.bookmark),
+            clear_queue: Self::str_to_key(&mut keybinds_as_str.clear_queue),
+            shuffle: Self::str_to_key(&mut keybinds_as_str.shuffle),
+            volume_up: Self::str_to_key(&mut keybinds_as_str.volume_up),
+            volume_down: Self::str_to_key(&mut keybinds_as_str.volume_down),
+            toggle_mute: Self::str_to_key(&mut keybinds_as_str.toggle_mute),
+            speed_up: Self::str_to_key(&mut keybinds_as_str.speed_up),
+            speed_down: Self::str_to_key(&mut keybinds_as_str.speed_down),
+            reset_speed: Self::str_to_key(&mut keybinds_as_str.reset_speed),
+            switch_player: Self::str_to_key(&mut keybinds_as_str.switch_player),
+            next_track: Self::str_to_key(&mut keybinds_as_str.next_track),
+            prev_track: Self::str_to_key(&mut keybinds_as_str.prev_track),
+            stop: Self::str_to_key(&mut keybinds_as_str.stop),
+            toggle_fullscreen: Self::str_to_key(&mut keybinds_as_str.toggle_fullscreen),
+            pick_format: Self::str_to_key(&mut keybinds_as_str.pick_format),
         }
     }
 
@@ -179,6 +359,7 @@ impl Config {
         match key.to_ascii_lowercase().as_str() {
             "enter" => Key::Char('\n'),
             "backspace" => Key::Backspace,
+            "tab" => Key::Char('\t'),
             "left" => Key::Left,
             "right" => Key::Right,
             "up" => Key::Up,
@@ -235,6 +416,129 @@ impl Default for Config {
         Config {
             keys: Keybinds::default(),
             keybinds: KeybindsAsStr::default(),
+            instance: None,
+            instances: vec![String::from(DEFAULT_INSTANCE)],
+            request_timeout: DEFAULT_REQUEST_TIMEOUT,
+            autoplay: false,
+            search_history_size: DEFAULT_SEARCH_HISTORY_SIZE,
+            watch_history: true,
+            restore_queue: false,
+            quality: Quality::default(),
+            proxy_streams: vec![],
+        }
+    }
+}
+
+/// Streams played by default, turned into youtube-dl formats for mpv.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Quality {
+    /// Highest video height, e.g. "720", or "best".
+    pub resolution: String,
+    /// Preferred video codec: "av1", "vp9", "h264" or "any".
+    pub codec: String,
+    /// Highest audio bitrate in kbit/s, 0 for the best available.
+    pub audio_bitrate: u32,
+}
+
+impl Quality {
+    /// Format of the video player: the preferred codec within the
+    /// resolution, then any codec, then a single file with both streams.
+    pub fn video_format(&self) -> String {
+        let height = match self.max_height() {
+            Some(height) => format!("[height<=?{}]", height),
+            None => String::new(),
+        };
+        let audio = self.best_audio();
+        let mut formats: Vec<String> = self.codec_prefixes()
+            .iter()
+            .map(|prefix| format!("bestvideo{}[vcodec^={}]+{}", height, prefix, audio))
+            .collect();
+        formats.push(format!("bestvideo{}+{}", height, audio));
+        formats.push(format!("best{}", height));
+        formats.push(String::from("best"));
+        formats.join("/")
+    }
+
+    /// Format of the audio player.
+    pub fn audio_format(&self) -> String {
+        format!("{}/bestaudio/best", self.best_audio())
+    }
+
+    fn best_audio(&self) -> String {
+        if self.audio_bitrate == 0 {
+            String::from("bestaudio")
+        }
+        else {
+            format!("bestaudio[abr<=?{}]", self.audio_bitrate)
+        }
+    }
+
+    fn max_height(&self) -> Option<u32> {
+        let resolution = self.resolution.trim_end_matches('p');
+        if resolution.eq_ignore_ascii_case("best") {
+            return None
+        }
+        match resolution.parse() {
+            Ok(height) => Some(height),
+            Err(_) => {
+                log::error!("Invalid resolution {}, using {}", self.resolution, DEFAULT_RESOLUTION);
+                Some(DEFAULT_RESOLUTION)
+            },
+        }
+    }
+
+    /// itag of the video-only stream played through an instance proxy: the
+    /// preferred codec (h264 for any) at the highest resolution within the
+    /// limit.
+    pub fn video_itag(&self) -> u32 {
+        // itags for 1080p, 720p, 480p and 360p.
+        let itags = match self.codec.to_ascii_lowercase().as_str() {
+            "av1" => [399, 398, 397, 396],
+            "vp9" => [248, 247, 244, 243],
+            _ => [137, 136, 135, 134],
+        };
+        let i = match self.max_height() {
+            Some(height) if height < 480 => 3,
+            Some(height) if height < 720 => 2,
+            Some(height) if height < 1080 => 1,
+            _ => 0,
+        };
+        itags[i]
+    }
+
+    /// itag of the audio stream played through an instance proxy.
+    pub fn audio_itag(&self) -> u32 {
+        match self.audio_bitrate {
+            0 => 251,
+            bitrate if bitrate >= 160 => 251,
+            bitrate if bitrate >= 128 => 140,
+            bitrate if bitrate >= 70 => 250,
+            _ => 249,
+        }
+    }
+
+    /// Prefixes of the `vcodec` youtube-dl reports for the preferred codec.
+    fn codec_prefixes(&self) -> &'static [&'static str] {
+        match self.codec.to_ascii_lowercase().as_str() {
+            "av1" => &["av01"],
+            "vp9" => &["vp9", "vp09"],
+            "h264" | "avc" => &["avc1"],
+            "any" | "" => &[],
+            codec => {
+                log::error!("Unknown codec {}, using any", codec);
+                &[]
+            },
+        }
+    }
+}
+
+impl Default for Quality {
+    fn default() -> Self {
+        Quality {
+            resolution: DEFAULT_RESOLUTION.to_string(),
+            codec: String::from("any"),
+            audio_bitrate: 0,
         }
     }
 }
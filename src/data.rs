@@ -3,25 +3,190 @@ use std::sync::{
     RwLock,
 };
 use crate::{
+    subscriptions::Subscriptions,
+    playlists::LocalPlaylists,
+    watch_history::WatchHistory,
+    bookmarks::Bookmarks,
     media::{
-        video::Video,
+        video::{
+            Video,
+            Format,
+        },
         playlist::Playlist,
         channel::Channel,
+        comment::{
+            Comments,
+            CommentSort,
+        },
     },
 };
 
+const SORT_VALUES: [(&str, &str); 6] = [
+    ("relevance", "relevance"),
+    ("rating", "rating"),
+    ("date", "upload_date"),
+    ("upload_date", "upload_date"),
+    ("views", "view_count"),
+    ("view_count", "view_count"),
+];
+const DATE_VALUES: [&str; 5] = ["hour", "today", "week", "month", "year"];
+const DURATION_VALUES: [&str; 3] = ["short", "medium", "long"];
+const TYPE_VALUES: [&str; 4] = ["video", "playlist", "channel", "all"];
+const FEATURE_VALUES: [&str; 10] = [
+    "hd", "subtitles", "creative_commons", "3d", "live", "purchased", "4k", "360", "location", "hdr",
+];
+
+/// Filters given as `key:value` words on the command line, e.g.
+/// `/rust async sort:date date:week duration:long features:hd,subtitles`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SearchFilters {
+    pub sort_by: Option<String>,
+    pub date: Option<String>,
+    pub duration: Option<String>,
+    pub search_type: Option<String>,
+    pub features: Vec<String>,
+}
+
+impl SearchFilters {
+    /// Splits the filters off a search, returning the remaining query. Words
+    /// with an unknown key are kept in the query.
+    pub fn parse(input: &str) -> Result<(String, SearchFilters), String> {
+        let mut filters = SearchFilters::default();
+        let mut query = vec![];
+
+        for word in input.split_whitespace() {
+            let mut parts = word.splitn(2, ':');
+            let key = parts.next().unwrap_or_default();
+            let value = parts.next().unwrap_or_default().to_ascii_lowercase();
+            match key {
+                "sort" | "sort_by" => {
+                    let sort = SORT_VALUES.iter()
+                        .find(|(alias, _)| *alias == value)
+                        .map(|(_, sort)| sort.to_string())
+                        .ok_or_else(|| invalid_value(key, &value, &["relevance", "rating", "date", "views"]))?;
+                    filters.sort_by = Some(sort);
+                },
+                "date" => filters.date = Some(checked_value(key, value, &DATE_VALUES)?),
+                "duration" => filters.duration = Some(checked_value(key, value, &DURATION_VALUES)?),
+                "type" => filters.search_type = Some(checked_value(key, value, &TYPE_VALUES)?),
+                "features" => {
+                    for feature in value.split(',').filter(|feature| !feature.is_empty()) {
+                        filters.features.push(checked_value(key, feature.to_owned(), &FEATURE_VALUES)?);
+                    }
+                },
+                _ => query.push(word),
+            }
+        }
+
+        Ok((query.join(" "), filters))
+    }
+
+    /// Whether results of `search_type` ("video", "playlist" or "channel")
+    /// should be requested at all.
+    pub fn includes(&self, search_type: &str) -> bool {
+        match self.search_type.as_deref() {
+            None | Some("all") => true,
+            Some(filter) => filter == search_type,
+        }
+    }
+
+    pub fn params(&self, query: &str, search_type: &str, page: u32) -> Vec<(&'static str, String)> {
+        let mut params = vec![
+            ("q", query.to_owned()),
+            ("page", page.to_string()),
+            ("type", search_type.to_owned()),
+            ("sort_by", self.sort_by.clone().unwrap_or_else(|| String::from("relevance"))),
+        ];
+        if let Some(date) = &self.date {
+            params.push(("date", date.clone()));
+        }
+        if let Some(duration) = &self.duration {
+            params.push(("duration", duration.clone()));
+        }
+        if !self.features.is_empty() {
+            params.push(("features", self.features.join(",")));
+        }
+        params
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == SearchFilters::default()
+    }
+}
+
+impl std::fmt::Display for SearchFilters {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let mut filters = vec![];
+        if let Some(sort_by) = &self.sort_by {
+            filters.push(format!("sort:{}", sort_by));
+        }
+        if let Some(date) = &self.date {
+            filters.push(format!("date:{}", date));
+        }
+        if let Some(duration) = &self.duration {
+            filters.push(format!("duration:{}", duration));
+        }
+        if let Some(search_type) = &self.search_type {
+            filters.push(format!("type:{}", search_type));
+        }
+        if !self.features.is_empty() {
+            filters.push(format!("features:{}", self.features.join(",")));
+        }
+        write!(f, "{}", filters.join(" "))
+    }
+}
+
+fn checked_value(key: &str, value: String, allowed: &[&str]) -> Result<String, String> {
+    if allowed.contains(&value.as_str()) {
+        Ok(value)
+    }
+    else {
+        Err(invalid_value(key, &value, allowed))
+    }
+}
+
+fn invalid_value(key: &str, value: &str, allowed: &[&str]) -> String {
+    format!("Invalid {} filter \"{}\", expected one of: {}", key, value, allowed.join(", "))
+}
+
+impl Default for SearchFilters {
+    fn default() -> Self {
+        SearchFilters {
+            sort_by: None,
+            date: None,
+            duration: None,
+            search_type: None,
+            features: vec![],
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Search {
     pub query: String,
+    pub filters: SearchFilters,
     pub videos: (Arc<RwLock<Vec<Video>>>, u32),
     pub playlists: (Arc<RwLock<Vec<Playlist>>>, u32),
     pub channels: (Arc<RwLock<Vec<Channel>>>, u32),
 }
 
+impl Search {
+    /// Title of the search view, listing the active filters.
+    pub fn title(&self) -> String {
+        if self.filters.is_empty() {
+            String::from("Search")
+        }
+        else {
+            format!("Search [{}]", self.filters)
+        }
+    }
+}
+
 impl Default for Search {
     fn default() -> Self {
         Search {
             query: String::new(),
+            filters: SearchFilters::default(),
             videos: (Arc::new(RwLock::new(vec![])), 1),
             playlists: (Arc::new(RwLock::new(vec![])), 1),
             channels: (Arc::new(RwLock::new(vec![])), 1),
@@ -37,6 +202,20 @@ pub struct LoadedData {
     pub top_videos: Vec<Video>,
     pub channel_videos: Vec<Video>,
     pub channel_playlists: Vec<Playlist>,
+    /// Id of the last opened video and its related videos.
+    pub related_videos: (String, Vec<Video>),
+    /// Formats of the last opened video, in the order of its formats tab.
+    pub video_formats: Vec<Format>,
+    pub formats_video: Option<Video>,
+    pub subscriptions: Subscriptions,
+    pub subscription_feed: Vec<Video>,
+    pub comments: Comments,
+    pub account_feed: Vec<Video>,
+    pub account_playlists: Vec<Playlist>,
+    pub playlists: LocalPlaylists,
+    pub opened_playlist: Option<String>,
+    pub watch_history: WatchHistory,
+    pub bookmarks: Bookmarks,
 }
 
 impl Default for LoadedData {
@@ -46,6 +225,18 @@ impl Default for LoadedData {
             playlist_videos: vec![],
             channel_videos: vec![],
             channel_playlists: vec![],
+            related_videos: (String::new(), vec![]),
+            video_formats: vec![],
+            formats_video: None,
+            subscriptions: Subscriptions::default(),
+            subscription_feed: vec![],
+            comments: Comments::new(String::new(), CommentSort::Top),
+            account_feed: vec![],
+            account_playlists: vec![],
+            playlists: LocalPlaylists::default(),
+            opened_playlist: None,
+            watch_history: WatchHistory::default(),
+            bookmarks: Bookmarks::default(),
             trending_videos: vec![],
             popular_videos: vec![],
             top_videos: vec![],
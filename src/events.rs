@@ -10,14 +10,17 @@ use termion::{
     event::Key,
     input::TermRead,
 };
+use crate::fetch::Fetched;
 
 
 pub enum Event<I> {
     Input(I),
     Tick,
+    Fetched(Box<Fetched>),
 }
 pub struct Events {
     rx: mpsc::Receiver<Event<Key>>,
+    tx: mpsc::Sender<Event<Key>>,
 }
 
 impl Default for Events {
@@ -35,15 +38,19 @@ impl Default for Events {
             });
         };
 
-        thread::spawn(move || {
-            loop {
-                tx.send(Event::Tick).unwrap();
-                thread::sleep(Duration::from_millis(250));
-            }
-        });
+        {
+            let tx = tx.clone();
+            thread::spawn(move || {
+                loop {
+                    tx.send(Event::Tick).unwrap();
+                    thread::sleep(Duration::from_millis(250));
+                }
+            });
+        };
         
         Events {
             rx,
+            tx,
         }
     }
 }
@@ -51,4 +58,8 @@ impl Events {
     pub fn next(&self) -> Result<Event<Key>, mpsc::RecvError> {
         self.rx.recv()
     }
+
+    pub fn sender(&self) -> mpsc::Sender<Event<Key>> {
+        self.tx.clone()
+    }
 }
@@ -0,0 +1,103 @@
+use termion::event::Key;
+use tokio::runtime::Runtime;
+use crate::{
+    events::Event,
+    data::{
+        Search,
+        SearchFilters,
+    },
+    invidious::{
+        ApiError,
+        Account,
+    },
+    ui::views::WindowType,
+    media::{
+        video::{
+            Video,
+            VideoDetails,
+        },
+        channel::Channel,
+        playlist::Playlist,
+        comment::{
+            CommentPage,
+            CommentSort,
+            CommentTarget,
+        },
+    },
+};
+use std::{
+    future::Future,
+    sync::mpsc::Sender,
+};
+
+pub enum Page {
+    Videos(Vec<Video>),
+    Playlists(Vec<Playlist>),
+    Channels(Vec<Channel>),
+}
+
+/// Results of requests made off the UI thread, delivered back to the main
+/// loop as `Event::Fetched`.
+pub enum Fetched {
+    Home(Result<(Vec<Video>, Vec<Video>), ApiError>),
+    Search(Result<Search, ApiError>),
+    Suggestions(String, Result<Vec<String>, ApiError>),
+    NextPage {
+        window_type: WindowType,
+        query: String,
+        filters: SearchFilters,
+        page: u32,
+        result: Result<Page, ApiError>,
+    },
+    Channel(String, Result<(Vec<Video>, Vec<Playlist>), ApiError>),
+    Playlist(String, Result<Vec<Video>, ApiError>),
+    Video(String, Result<VideoDetails, ApiError>),
+    Autoplay(String, Result<VideoDetails, ApiError>),
+    SubscriptionFeed(Vec<Video>, Vec<(String, ApiError)>),
+    Account(Result<Account, ApiError>),
+    Subscribed {
+        author: String,
+        author_id: String,
+        subscribed: bool,
+        result: Result<(), ApiError>,
+    },
+    Comments {
+        video_id: String,
+        sort: CommentSort,
+        target: CommentTarget,
+        result: Result<CommentPage, ApiError>,
+    },
+    PlaylistQueue {
+        title: String,
+        is_video: bool,
+        result: Result<Vec<Video>, ApiError>,
+    },
+    ProxyCheck(String, Result<bool, ApiError>),
+}
+
+pub struct Fetcher {
+    runtime: Runtime,
+    tx: Sender<Event<Key>>,
+}
+
+impl Fetcher {
+    pub fn new(tx: Sender<Event<Key>>) -> Self {
+        Fetcher {
+            runtime: Runtime::new().unwrap(),
+            tx,
+        }
+    }
+
+    pub fn spawn<F>(&self, fut: F)
+    where
+        F: Future<Output = Fetched> + Send + 'static,
+    {
+        let tx = self.tx.clone();
+        self.runtime.spawn(async move {
+            let fetched = fut.await;
+            if tx.send(Event::Fetched(Box::new(fetched))).is_err() {
+                log::error!("Unable to deliver fetched data, event loop is gone");
+            }
+        });
+    }
+}
@@ -12,73 +12,225 @@ use crate::{
         ContentType,
         ViewType,
         WindowType,
+        View,
+        Window,
     },
+    data::{
+        Search,
+        SearchFilters,
+    },
+    media::{
+        Media,
+        ListItem,
+        playlist::Playlist,
+        video::best_audio,
+        comment::{
+            Comments,
+            CommentSort,
+            CommentTarget,
+        },
+    },
+    fetch::{
+        Fetched,
+        Page,
+    },
+    input::{
+        LineEditor,
+        InputMode,
+        MAX_SUGGESTIONS,
+    },
+    queue::QueueEntry,
+    player::PlayerTarget,
+    commands,
     utils,
-    invidious,
 };
 use failure::Error;
-use std::sync::{
-    Arc,
-    RwLock,
-};
 
-fn cmdline_handler<B: Backend>(key: Key, mut app: &mut App, _terminal: &mut Terminal<B>) -> Result<(), Error> {
-    if key == app.config.keys.submit_entry {
-        if let Some(view) = app.view_list.get_mut(&ViewType::Search) {
-            let input = app.input.clone();
-            let client = &app.client;
-            let search_data = app.runtime.write().unwrap().block_on(invidious::search(input, &client));
-            let search_data = search_data.unwrap();
+/// Number of autoplayed videos remembered to avoid bouncing between the same
+/// few related videos.
+const AUTOPLAY_HISTORY_LEN: usize = 50;
+const VOLUME_STEP: i64 = 5;
+const SPEED_STEP: f64 = 0.1;
+const SEEK_STEP: i64 = 5;
 
-            let video_text = utils::video_to_text(search_data.videos.0.read().unwrap().clone());
-            let playlist_text = utils::playlist_to_text(search_data.playlists.0.read().unwrap().clone());
-            let channel_text = utils::channel_to_text(search_data.channels.0.read().unwrap().clone());
-            
-            if let Some(mut window) = view.root_windows.get_mut(0) {
-                window.content = ContentType::MediaContent(Arc::new(RwLock::new(video_text)));
-                window.selected = 0;
-            }
-            if let Some(mut window) = view.root_windows.get_mut(1) {
-                window.content = ContentType::MediaContent(Arc::new(RwLock::new(playlist_text)));
-                window.selected = 0;
-            }
+fn cmdline_handler<B: Backend>(key: Key, mut app: &mut App, _terminal: &mut Terminal<B>) -> Result<(), Error> {
+    if app.search_history.reverse_search.is_some() && reverse_search_handler(key, app) {
+        return Ok(())
+    }
 
-            if let Some(mut window) = view.root_windows.get_mut(2) {
-                window.content = ContentType::MediaContent(Arc::new(RwLock::new(channel_text)));
-                window.selected = 0;
-            }
-        
-            view.view_stack = vec![];
-            app.focused_view = ViewType::Search;
-            app.loaded_data.search_data = search_data;
+    if key == app.config.keys.submit_entry {
+        let line = app.input.text().to_owned();
+        match app.input.mode {
+            InputMode::Search => submit_search(app, line),
+            InputMode::Command => {
+                close_cmdline(app);
+                commands::run(app, &line);
+            },
         }
-
-
-        app.input = String::new();
-        app.cmdline_focused = false;
     }
-    else if let Key::Char(c) = key {
-        app.input.push(c);
+    else if key == Key::Ctrl('r') && app.input.mode == InputMode::Search {
+        app.search_history.start_reverse_search();
+    }
+    else if key == Key::Up && app.input.mode == InputMode::Search {
+        let current = app.input.text().to_owned();
+        if let Some(query) = app.search_history.prev(&current) {
+            let query = query.to_owned();
+            app.input.set_text(query);
+            app.suggestions.clear();
+        }
+    }
+    else if key == Key::Down && app.input.mode == InputMode::Search {
+        if let Some(query) = app.search_history.next() {
+            let query = query.to_owned();
+            app.input.set_text(query);
+            app.suggestions.clear();
+        }
+    }
+    else if key == Key::Char('\t') {
+        if let Some(suggestion) = app.suggestions.select_next() {
+            let suggestion = suggestion.to_owned();
+            complete_suggestion(app, suggestion);
+        }
+    }
+    else if key == Key::BackTab {
+        if let Some(suggestion) = app.suggestions.select_prev() {
+            let suggestion = suggestion.to_owned();
+            complete_suggestion(app, suggestion);
+        }
+    }
+    else if key == Key::Left {
+        app.input.move_left();
+    }
+    else if key == Key::Right {
+        app.input.move_right();
+    }
+    else if key == Key::Home || key == Key::Ctrl('a') {
+        app.input.move_home();
+    }
+    else if key == Key::End || key == Key::Ctrl('e') {
+        app.input.move_end();
+    }
+    else if key == Key::Ctrl('w') {
+        app.input.delete_word();
+        app.suggestions.input_changed();
+    }
+    else if key == Key::Ctrl('u') {
+        app.input.delete_to_start();
+        app.suggestions.input_changed();
+    }
+    else if key == Key::Delete {
+        app.input.delete_at();
+        app.suggestions.input_changed();
     }
     else if key == Key::Backspace {
-        app.input.pop();
         if app.input.is_empty() {
-            app.cmdline_focused = false;
+            close_cmdline(app);
+        }
+        else {
+            app.input.delete_before();
+            app.suggestions.input_changed();
         }
     }
     else if key == app.config.keys.back {
-        app.input = String::new();
-        app.cmdline_focused = false;
+        close_cmdline(app);
+    }
+    else if let Key::Char(c) = key {
+        app.input.insert(c);
+        app.suggestions.input_changed();
     }
     Ok(())
 }
 
+/// Handles keys while a Ctrl-R search is active. Keys that aren't part of the
+/// search accept the current match and return false so they still apply.
+fn reverse_search_handler(key: Key, app: &mut App) -> bool {
+    match key {
+        Key::Ctrl('r') => app.search_history.reverse_search_next(),
+        Key::Backspace => app.search_history.reverse_search_pop(),
+        _ if key == app.config.keys.back => app.search_history.reverse_search = None,
+        Key::Char(c) if key != app.config.keys.submit_entry && c != '\t' => app.search_history.reverse_search_push(c),
+        _ => {
+            if let Some(query) = app.search_history.reverse_search_match() {
+                let query = query.to_owned();
+                app.input.set_text(query);
+            }
+            app.search_history.reverse_search = None;
+            return false
+        },
+    }
+    true
+}
+
+fn submit_search(app: &mut App, line: String) {
+    let (query, filters) = match SearchFilters::parse(&line) {
+        Ok(search) => search,
+        Err(e) => {
+            app.notify_error(e);
+            return
+        },
+    };
+
+    app.search_history.push(&line);
+    if let Err(e) = app.search_history.save() {
+        app.notify_error(format!("Unable to save search history: {}", e));
+    }
+
+    if let Some(view) = app.view_list.get_mut(&ViewType::Search) {
+        for window in view.root_windows.iter_mut() {
+            window.set_content(vec![]);
+            window.loading = true;
+        }
+    
+        view.view_stack = vec![];
+        app.focused_view = ViewType::Search;
+        app.loaded_data.search_data = Search {
+            query: query.clone(),
+            filters: filters.clone(),
+            ..Search::default()
+        };
+        view.tabs.title = app.loaded_data.search_data.title();
+
+        let api = app.api.clone();
+        app.fetcher.spawn(async move {
+            Fetched::Search(api.search(query, filters).await)
+        });
+    }
+
+    close_cmdline(app);
+}
+
+fn close_cmdline(app: &mut App) {
+    app.input = LineEditor::default();
+    app.suggestions.clear();
+    app.search_history.reset();
+    app.cmdline_focused = false;
+}
+
+/// Replaces the query with a suggestion, keeping any filters already typed.
+/// Commands are replaced as a whole.
+fn complete_suggestion(app: &mut App, suggestion: String) {
+    if app.input.mode != InputMode::Search {
+        app.input.set_text(suggestion);
+        return
+    }
+    let filters = SearchFilters::parse(app.input.text())
+        .map(|(_, filters)| filters)
+        .unwrap_or_default();
+    if filters.is_empty() {
+        app.input.set_text(suggestion);
+    }
+    else {
+        app.input.set_text(format!("{} {}", suggestion, filters));
+    }
+}
+
 pub fn event_handler<B: Backend>(key: Key, mut app: &mut App, _terminal: &mut Terminal<B>) -> Result<(), Error> {
     if app.cmdline_focused {
         cmdline_handler(key, app, _terminal)?;
     }
     else if key == app.config.keys.quit {
         app.quit = true;
+        app.save_queues(true);
         app.player.stop_all();
     }
     else if key == app.config.keys.home_view {
@@ -90,14 +242,96 @@ pub fn event_handler<B: Backend>(key: Key, mut app: &mut App, _terminal: &mut Te
     else if key == app.config.keys.queue_view {
         app.focused_view = ViewType::Queue;
     }
+    else if key == app.config.keys.subscriptions_view {
+        app.focused_view = ViewType::Subscriptions;
+        utils::update_subscriptions_view(app);
+        utils::load_subscription_feed(app);
+    }
+    else if key == app.config.keys.playlists_view {
+        app.focused_view = ViewType::Playlists;
+        utils::update_playlists_view(app);
+    }
+    else if key == app.config.keys.history_view {
+        app.focused_view = ViewType::History;
+        utils::update_history_view(app);
+    }
+    else if key == app.config.keys.bookmarks_view {
+        app.focused_view = ViewType::Bookmarks;
+        utils::update_bookmarks_view(app);
+    }
     else if key == app.config.keys.search {
         app.cmdline_focused = true;
         //terminal.show_cursor()?;
-        app.input = String::new();
-        if let Key::Char(c) = key {
-            app.input.push(c);
+        app.input = match key {
+            Key::Char(c) => LineEditor::new(InputMode::Search, &c.to_string()),
+            _ => LineEditor::default(),
+        };
+        app.suggestions.clear();
+    }
+    else if key == app.config.keys.command {
+        app.cmdline_focused = true;
+        app.input = match key {
+            Key::Char(c) => LineEditor::new(InputMode::Command, &c.to_string()),
+            _ => LineEditor::new(InputMode::Command, ""),
+        };
+        app.suggestions.clear();
+    }
+    else if key == app.config.keys.add_to_playlist {
+        let video = utils::focused_window(app).and_then(|window| utils::get_video(window, &app.loaded_data));
+        if video.is_some() {
+            open_command(app, String::from("playlist add "));
+            app.suggest_playlists();
+        }
+        else {
+            app.notify_error("Selection is not a video");
+        }
+    }
+    else if key == app.config.keys.pick_format {
+        pick_format(app);
+    }
+    else if key == app.config.keys.bookmark {
+        let bookmarkable = utils::focused_window(app).and_then(|window| utils::get_bookmarkable(window, &app.loaded_data));
+        match bookmarkable {
+            Some(bookmarkable) => {
+                let title = bookmarkable.title().to_owned();
+                let bookmarked = app.loaded_data.bookmarks.toggle(bookmarkable);
+                match app.loaded_data.bookmarks.save() {
+                    Ok(_) if bookmarked => app.notify_info(format!("Bookmarked {}", title)),
+                    Ok(_) => app.notify_info(format!("Removed bookmark {}", title)),
+                    Err(e) => app.notify_error(format!("Unable to save bookmarks: {}", e)),
+                }
+                utils::update_bookmarks_view(app);
+            },
+            None => app.notify_error("Selection cannot be bookmarked"),
+        }
+    }
+    else if key == app.config.keys.rename_playlist && is_local_playlist_window(focused_window_type(app)) {
+        let playlist = utils::focused_window(app).and_then(|window| utils::get_local_playlist(window, &app.loaded_data));
+        if let Some(playlist) = playlist {
+            open_command(app, format!("playlist rename {}", playlist.name));
         }
     }
+    else if key == app.config.keys.delete_playlist && focused_window_type(app) == Some(WindowType::LocalPlaylists) {
+        let playlist = utils::focused_window(app).and_then(|window| utils::get_local_playlist(window, &app.loaded_data));
+        if let Some(playlist) = playlist {
+            app.loaded_data.playlists.delete(&playlist.name);
+            commands::save_playlists(app, format!("Deleted playlist {}", playlist.name));
+        }
+    }
+    else if (key == app.config.keys.move_video_down || key == app.config.keys.move_video_up || key == app.config.keys.remove_from_playlist)
+        && focused_window_type(app) == Some(WindowType::LocalPlaylistVideos)
+    {
+        edit_playlist_videos(app, key);
+    }
+    else if (key == app.config.keys.move_video_down || key == app.config.keys.move_video_up || key == app.config.keys.remove_from_playlist
+        || key == app.config.keys.clear_queue || key == app.config.keys.submit_entry)
+        && is_queue_window(focused_window_type(app))
+    {
+        edit_queue(app, key);
+    }
+    else if key == app.config.keys.open_selection && focused_window_type(app) == Some(WindowType::LocalPlaylists) {
+        utils::open_local_playlist(app);
+    }
     else if key == app.config.keys.back {
         if let Some(view) = app.view_list.get_mut(&app.focused_view) {
             view.pop_stack();
@@ -106,19 +340,20 @@ pub fn event_handler<B: Backend>(key: Key, mut app: &mut App, _terminal: &mut Te
     else if key == app.config.keys.move_down {
         if let Some(root_view) = app.view_list.get_mut(&app.focused_view) {
             if let Some(view) = root_view.get_current_view_mut() { 
+                let media_id = view.media_id.clone().unwrap_or_default();
                 if let Some(mut window) = view.root_windows.get_mut(view.tabs.selected) {
                     let content_len = match &window.content {
                         ContentType::ListContent(vec) => vec.len(),
+                        ContentType::TextContent(vec) => vec.len(),
                         ContentType::MediaContent(vec) => vec.read().unwrap().len(),
                     };
                     utils::next_selection(&mut window, content_len);
                     if window.selected == content_len.saturating_sub(4) {
-                        match utils::fetch_next_page(app.client.clone(), app.runtime.clone(), &app.loaded_data, window) {
-                            Ok(_) => {
-                                app.loaded_data.search_data.videos.1 += 1;
-                                log::info!("Fetched next page of focused window");
-                            },
-                            Err(e) => log::error!("Error fetching next page: {}", e),
+                        if window.window_type == WindowType::VideoComments {
+                            utils::fetch_next_comments(&app.api, &app.fetcher, &app.loaded_data.comments, &media_id, window);
+                        }
+                        else {
+                            utils::fetch_next_page(&app.api, &app.fetcher, &app.loaded_data, window);
                         }
                     }
                 }
@@ -151,121 +386,146 @@ pub fn event_handler<B: Backend>(key: Key, mut app: &mut App, _terminal: &mut Te
             }
         }
     }
-    else if key == app.config.keys.submit_entry {
-        if let Some(root_view) = app.view_list.get(&app.focused_view) {
-            if let Some(view) = root_view.get_current_view() {
-                if let Some(window) = view.root_windows.get(view.tabs.selected) {
-                    let media = utils::get_media(&window, &app.loaded_data);
-                    if let Some(media) = media {
-                        if !app.video_queue.is_empty() {
-                            app.video_queue.clear();
+    else if key == app.config.keys.submit_entry && focused_window_type(app) == Some(WindowType::VideoComments) {
+        if let Some(root_view) = app.view_list.get_mut(&app.focused_view) {
+            if let Some(view) = root_view.get_current_view_mut() {
+                let video_id = view.media_id.clone().unwrap_or_default();
+                if let Some(window) = view.root_windows.get_mut(view.tabs.selected) {
+                    let comments = &mut app.loaded_data.comments;
+                    if comments.video_id != video_id {
+                        utils::fetch_next_comments(&app.api, &app.fetcher, comments, &video_id, window);
+                    }
+                    else if let Some(entry) = comments.entries.get(window.selected) {
+                        if entry.expanded {
+                            comments.collapse(window.selected);
+                            window.update_content(comments.to_text());
                         }
-                        match window.window_type {
-                            WindowType::SearchPlaylists | WindowType::ChannelPlaylists => {
-                                let client = &app.client;
-                                let runtime = &mut app.runtime;
-                                let view = media.open(client, runtime.clone(), &mut app.loaded_data).unwrap();
-                                if let Some(window) = view.root_windows.get(0) {
-                                    if let ContentType::MediaContent(ref content) = window.content {
-                                        let mut text = content.read().unwrap().iter().map(|text| {
-                                            (text[0].clone(), text[1].clone(), Some(window.title.clone()))
-                                        }).collect();
-                                        app.video_queue.push_back((media.title(), media.author(), None));
-                                        app.video_queue.append(&mut text);
-                                    }
-                                }
-                            },
-                            _ => app.video_queue.push_back((media.title(), media.author(), None)),
-
+                        else if let Some(continuation) = entry.reply_continuation() {
+                            window.loading = true;
+                            let target = CommentTarget::Replies(continuation.to_owned());
+                            utils::fetch_comments(&app.api, &app.fetcher, video_id, comments.sort, target);
                         }
-                        media.play_video(&mut app.player);
                     }
                 }
             }
         }
     }
-    else if key == app.config.keys.play_pause {
-        app.player.toggle_audio_playback();
-    }
-    else if key == app.config.keys.audio_only {
-        if let Some(root_view) = app.view_list.get(&app.focused_view) {
-            if let Some(view) = root_view.get_current_view() {
-                if let Some(window) = view.root_windows.get(view.tabs.selected) {
-                    let media = utils::get_media(&window, &app.loaded_data);
-                    if let Some(media) = media {
-                        if !app.audio_queue.is_empty() {
-                            app.audio_queue.clear();
-                        }
-                        match window.window_type {
-                            WindowType::SearchPlaylists | WindowType::ChannelPlaylists => {
-                                let client = &app.client;
-                                let runtime = &mut app.runtime;
-                                let view = media.open(client, runtime.clone(), &mut app.loaded_data).unwrap();
-                                if let Some(window) = view.root_windows.get(0) {
-                                    if let ContentType::MediaContent(ref content) = window.content {
-                                        let mut text = content.read().unwrap().iter().map(|text| {
-                                            (text[0].clone(), text[1].clone(), Some(window.title.clone()))
-                                        }).collect();
-                                        app.audio_queue.push_back((media.title(), media.author(), None));
-                                        app.audio_queue.append(&mut text);
-                                    }
-                                }
-                            },
-                            _ => app.audio_queue.push_back((media.title(), media.author(), None)),
-
-                        }
-                        media.play_audio(&mut app.player);
+    else if key == app.config.keys.toggle_comment_sort && focused_window_type(app) == Some(WindowType::VideoComments) {
+        if let Some(root_view) = app.view_list.get_mut(&app.focused_view) {
+            if let Some(view) = root_view.get_current_view_mut() {
+                let video_id = view.media_id.clone().unwrap_or_default();
+                if let Some(window) = view.root_windows.get_mut(view.tabs.selected) {
+                    let sort = if app.loaded_data.comments.video_id == video_id {
+                        app.loaded_data.comments.sort.toggle()
                     }
+                    else {
+                        CommentSort::Top
+                    };
+                    app.loaded_data.comments = Comments::new(video_id.clone(), sort);
+                    window.title = format!("{} ({})", window.title.trim_end_matches(" (top)").trim_end_matches(" (new)"), sort.as_param());
+                    window.set_content(vec![]);
+                    window.loading = true;
+                    utils::fetch_comments(&app.api, &app.fetcher, video_id, sort, CommentTarget::Reload);
                 }
             }
         }
     }
-    else if key == app.config.keys.queue_audio {
-        if let Some(root_view) = app.view_list.get(&app.focused_view) {
-            if let Some(view) = root_view.get_current_view() {
-                if let Some(window) = view.root_windows.get(view.tabs.selected) {
-                    let media = utils::get_media(&window, &app.loaded_data);
-                    if let Some(media) = media {
-                        match window.window_type {
-                            WindowType::SearchPlaylists | WindowType::ChannelPlaylists => app.audio_queue.push_back((media.title(), media.author(), Some(window.title.clone()))),
-                            _ => app.audio_queue.push_back((media.title(), media.author(), None)),
-                        }
-
-                        app.player.queue_audio(media.url());
-                    }
-                }
-            }
+    else if key == app.config.keys.submit_entry {
+        play_selection(app, true);
+    }
+    else if key == app.config.keys.play_pause {
+        app.player.toggle_playback();
+    }
+    else if key == app.config.keys.switch_player {
+        match app.player.toggle_target() {
+            PlayerTarget::Audio => app.notify_info("Controlling the audio player"),
+            PlayerTarget::Video => app.notify_info("Controlling the video player"),
+        }
+    }
+    else if key == app.config.keys.next_track {
+        app.player.playlist_next();
+    }
+    else if key == app.config.keys.prev_track {
+        app.player.playlist_prev();
+    }
+    else if key == app.config.keys.stop {
+        app.player.stop();
+    }
+    else if key == app.config.keys.toggle_fullscreen {
+        if !app.player.toggle_fullscreen() {
+            app.notify_error("No video is playing");
         }
     }
+    else if key == app.config.keys.audio_only {
+        play_selection(app, false);
+    }
+    else if key == app.config.keys.queue_audio {
+        queue_selection(app, false);
+    }
     else if key == app.config.keys.queue_video {
-        if let Some(root_view) = app.view_list.get(&app.focused_view) {
-            if let Some(view) = root_view.get_current_view() {
+        queue_selection(app, true);
+    }
+    else if key == app.config.keys.open_selection {
+        if let Some(root_view) = app.view_list.get_mut(&app.focused_view) {
+            if let Some(view) = root_view.get_current_view_mut() {
                 if let Some(window) = view.root_windows.get(view.tabs.selected) {
                     let media = utils::get_media(&window, &app.loaded_data);
                     if let Some(media) = media {
-                        match window.window_type {
-                            WindowType::SearchPlaylists | WindowType::ChannelPlaylists => app.video_queue.push_back((media.title(), media.author(), Some(window.title.clone()))),
-                            _ =>  app.video_queue.push_back((media.title(), media.author(), None)),
+                        if let Some(new_view) = media.open(&app.api, &app.fetcher) {
+                            root_view.view_stack.push(new_view);
                         }
-
-                        app.player.queue_video(media.url());
                     }
                 }
             }
         }
     }
-    else if key == app.config.keys.open_selection {
-        if let Some(root_view) = app.view_list.get_mut(&app.focused_view) {
-            if let Some(view) = root_view.get_current_view_mut() {
-                if let Some(window) = view.root_windows.get(view.tabs.selected) {
-                    let media = utils::get_media(&window, &app.loaded_data);
-                    if let Some(media) = media {
-                        let client = &app.client;
-                        let runtime = &mut app.runtime;
-                        let new_view = media.open(client, runtime.clone(), &mut app.loaded_data).unwrap();
-                        root_view.view_stack.push(new_view);
+    else if key == app.config.keys.toggle_autoplay {
+        app.autoplay = !app.autoplay;
+        app.autoplay_source = None;
+        if app.autoplay {
+            app.notify_info("Autoplay of related videos enabled");
+        }
+        else {
+            app.notify_info("Autoplay of related videos disabled");
+        }
+    }
+    else if key == app.config.keys.subscribe {
+        let media = app.view_list
+            .get(&app.focused_view)
+            .and_then(|root_view| root_view.get_current_view())
+            .and_then(|view| view.root_windows.get(view.tabs.selected))
+            .and_then(|window| utils::get_media(window, &app.loaded_data));
+        if let Some(media) = media {
+            let (author, author_id) = (media.author(), media.author_id());
+            if author_id.is_empty() {
+                app.notify_error("Selection has no channel to subscribe to");
+            }
+            else if app.api.is_logged_in() {
+                let subscribed = !app.loaded_data.subscriptions.is_subscribed(&author_id);
+                let api = app.api.clone();
+                app.fetcher.spawn(async move {
+                    let result = if subscribed {
+                        api.subscribe(&author_id).await
+                    }
+                    else {
+                        api.unsubscribe(&author_id).await
+                    };
+                    Fetched::Subscribed {
+                        author,
+                        author_id,
+                        subscribed,
+                        result,
                     }
+                });
+            }
+            else {
+                let subscribed = app.loaded_data.subscriptions.toggle(author.clone(), author_id);
+                match app.loaded_data.subscriptions.save() {
+                    Ok(_) if subscribed => app.notify_info(format!("Subscribed to {}", author)),
+                    Ok(_) => app.notify_info(format!("Unsubscribed from {}", author)),
+                    Err(e) => app.notify_error(format!("Unable to save subscriptions: {}", e)),
                 }
+                utils::update_subscriptions_view(app);
             }
         }
     }
@@ -275,18 +535,37 @@ pub fn event_handler<B: Backend>(key: Key, mut app: &mut App, _terminal: &mut Te
     else if key == app.config.keys.loop_playlist_audio {
         app.player.toggle_loop_playlist_audio();
     }
+    else if key == app.config.keys.shuffle {
+        app.cycle_shuffle();
+    }
+    else if key == app.config.keys.volume_up {
+        app.player.change_volume(VOLUME_STEP);
+    }
+    else if key == app.config.keys.volume_down {
+        app.player.change_volume(-VOLUME_STEP);
+    }
+    else if key == app.config.keys.toggle_mute {
+        app.player.toggle_mute();
+    }
+    else if key == app.config.keys.speed_up {
+        app.player.change_speed(SPEED_STEP);
+    }
+    else if key == app.config.keys.speed_down {
+        app.player.change_speed(-SPEED_STEP);
+    }
+    else if key == app.config.keys.reset_speed {
+        app.player.set_speed(1.0);
+    }
     else if key == app.config.keys.copy_url {
         if let Some(root_view) = app.view_list.get_mut(&app.focused_view) {
             if let Some(view) = root_view.get_current_view_mut() {
                 if let Some(window) = view.root_windows.get(view.tabs.selected) {
                     let media = utils::get_media(&window, &app.loaded_data);
                     if let Some(media) = media {
-                        match app.clipboard.set_contents(media.url()) {
-                            Ok(_) => {
-                                log::info!("Yanked {} to clipboard", media.url());
-                                app.input = format!("Yanked {} to clipboard", media.url());
-                            },
-                            Err(e) => log::error!("Could not yank {} to clipboard: {:#?}", media.url(), e),
+                        let url = media.url(&app.api);
+                        match app.clipboard.set_contents(url.clone()) {
+                            Ok(_) => app.notify_info(format!("Yanked {} to clipboard", url)),
+                            Err(e) => log::error!("Could not yank {} to clipboard: {:#?}", url, e),
                         }
                     }
                 }
@@ -294,11 +573,653 @@ pub fn event_handler<B: Backend>(key: Key, mut app: &mut App, _terminal: &mut Te
         }
     }
     else if key == app.config.keys.seek_audio_forward {
-        app.player.seek_audio("5");
+        app.player.seek(SEEK_STEP);
     }
     else if key == app.config.keys.seek_audio_backward {
-        app.player.seek_audio("-5");
+        app.player.seek(-SEEK_STEP);
     }
     
     Ok(())
 }
+
+pub fn fetch_handler(fetched: Fetched, app: &mut App) {
+    match fetched {
+        Fetched::Home(result) => {
+            match result {
+                Ok((trending, popular)) => {
+                    if let Some(view) = app.view_list.get_mut(&ViewType::Home) {
+                        if let Some(window) = view.window_mut(WindowType::TrendingVideos) {
+                            window.set_content(utils::video_to_text(trending.clone()));
+                        }
+                        if let Some(window) = view.window_mut(WindowType::PopularVideos) {
+                            window.set_content(utils::video_to_text(popular.clone()));
+                        }
+                    }
+                    app.loaded_data.trending_videos = trending;
+                    app.loaded_data.popular_videos = popular;
+                },
+                Err(e) => {
+                    stop_loading(app.view_list.get_mut(&ViewType::Home));
+                    app.notify_error(format!("Unable to load home: {}", e));
+                },
+            }
+        },
+        Fetched::Search(result) => {
+            match result {
+                Ok(search_data) => {
+                    if search_data.query != app.loaded_data.search_data.query || search_data.filters != app.loaded_data.search_data.filters {
+                        log::info!("Discarding stale results for {}", search_data.query);
+                        return
+                    }
+
+                    if let Some(view) = app.view_list.get_mut(&ViewType::Search) {
+                        if let Some(window) = view.window_mut(WindowType::SearchVideos) {
+                            window.set_content(utils::video_to_text(search_data.videos.0.read().unwrap().clone()));
+                        }
+                        if let Some(window) = view.window_mut(WindowType::SearchPlaylists) {
+                            window.set_content(utils::playlist_to_text(search_data.playlists.0.read().unwrap().clone()));
+                        }
+                        if let Some(window) = view.window_mut(WindowType::SearchChannels) {
+                            window.set_content(utils::channel_to_text(search_data.channels.0.read().unwrap().clone()));
+                        }
+                    }
+                    app.loaded_data.search_data = search_data;
+                },
+                Err(e) => {
+                    stop_loading(app.view_list.get_mut(&ViewType::Search));
+                    app.notify_error(format!("Search failed: {}", e));
+                },
+            }
+        },
+        Fetched::NextPage { window_type, query, filters, page, result } => {
+            let search_data = &app.loaded_data.search_data;
+            if query != search_data.query || filters != search_data.filters {
+                return
+            }
+
+            let search_data = &mut app.loaded_data.search_data;
+            let text = match result {
+                Ok(Page::Videos(mut videos)) => {
+                    let mut current = search_data.videos.0.write().unwrap();
+                    current.append(&mut videos);
+                    search_data.videos.1 = page;
+                    Some(utils::video_to_text(current.clone()))
+                },
+                Ok(Page::Playlists(mut playlists)) => {
+                    let mut current = search_data.playlists.0.write().unwrap();
+                    current.append(&mut playlists);
+                    search_data.playlists.1 = page;
+                    Some(utils::playlist_to_text(current.clone()))
+                },
+                Ok(Page::Channels(mut channels)) => {
+                    let mut current = search_data.channels.0.write().unwrap();
+                    current.append(&mut channels);
+                    search_data.channels.1 = page;
+                    Some(utils::channel_to_text(current.clone()))
+                },
+                Err(e) => {
+                    app.notify_error(format!("Unable to fetch page {}: {}", page, e));
+                    None
+                },
+            };
+
+            if let Some(view) = app.view_list.get_mut(&ViewType::Search) {
+                if let Some(window) = view.window_mut(window_type) {
+                    window.loading = false;
+                    if let (Some(text), ContentType::MediaContent(content)) = (text, &window.content) {
+                        *content.write().unwrap() = text;
+                    }
+                }
+            }
+        },
+        Fetched::Channel(id, result) => {
+            let view = app.view_list
+                .values_mut()
+                .find_map(|view| view.find_media_view_mut(&id));
+            let view = match view {
+                Some(view) => view,
+                None => return,
+            };
+
+            match result {
+                Ok((videos, playlists)) => {
+                    if let Some(window) = view.window_mut(WindowType::ChannelVideos) {
+                        window.set_content(utils::video_to_text(videos.clone()));
+                    }
+                    if let Some(window) = view.window_mut(WindowType::ChannelPlaylists) {
+                        window.set_content(utils::playlist_to_text(playlists.clone()));
+                    }
+                    app.loaded_data.channel_videos = videos;
+                    app.loaded_data.channel_playlists = playlists;
+                },
+                Err(e) => {
+                    stop_loading(Some(view));
+                    app.notify_error(format!("Unable to open channel: {}", e));
+                },
+            }
+        },
+        Fetched::Playlist(id, result) => {
+            let view = app.view_list
+                .values_mut()
+                .find_map(|view| view.find_media_view_mut(&id));
+            let view = match view {
+                Some(view) => view,
+                None => return,
+            };
+
+            match result {
+                Ok(videos) => {
+                    if let Some(window) = view.window_mut(WindowType::PlaylistVideos) {
+                        window.set_content(utils::video_to_text(videos.clone()));
+                    }
+                    app.loaded_data.playlist_videos = videos;
+                },
+                Err(e) => {
+                    stop_loading(Some(view));
+                    app.notify_error(format!("Unable to open playlist: {}", e));
+                },
+            }
+        },
+        Fetched::Video(id, result) => {
+            let view = app.view_list
+                .values_mut()
+                .find_map(|view| view.find_media_view_mut(&id));
+            let view = match view {
+                Some(view) => view,
+                None => return,
+            };
+
+            match result {
+                Ok(details) => {
+                    if let Some(window) = view.window_mut(WindowType::VideoDetails) {
+                        window.set_text(details.to_lines());
+                    }
+                    if let Some(window) = view.window_mut(WindowType::VideoRelated) {
+                        window.set_content(utils::related_to_text(details.recommended_videos.clone()));
+                    }
+                    let formats = details.formats();
+                    if let Some(window) = view.window_mut(WindowType::VideoFormats) {
+                        window.set_content(formats.iter().map(|format| format.into_text()).collect());
+                    }
+                    app.loaded_data.video_formats = formats;
+                    app.loaded_data.formats_video = Some(details.to_video());
+                    app.loaded_data.related_videos = (id, details.recommended_videos);
+                },
+                Err(e) => {
+                    stop_loading(Some(view));
+                    app.loaded_data.related_videos = (id, vec![]);
+                    app.notify_error(format!("Unable to open video: {}", e));
+                },
+            }
+        },
+        Fetched::Suggestions(query, result) => {
+            if !app.cmdline_focused || query != app.suggestions.requested {
+                return
+            }
+
+            match result {
+                Ok(mut suggestions) => {
+                    suggestions.truncate(MAX_SUGGESTIONS);
+                    app.suggestions.items = suggestions;
+                    app.suggestions.selected = None;
+                },
+                Err(e) => log::error!("Unable to fetch suggestions for {}: {}", query, e),
+            }
+        },
+        Fetched::SubscriptionFeed(videos, failed) => {
+            let visited = app.loaded_data.subscriptions.visit();
+            let last_visit = app.loaded_data.subscriptions.new_since();
+            if let Some(view) = app.view_list.get_mut(&ViewType::Subscriptions) {
+                if let Some(window) = view.window_mut(WindowType::SubscriptionFeed) {
+                    window.set_content(utils::video_to_text(videos.clone()));
+                    window.highlighted = videos.iter()
+                        .enumerate()
+                        .filter(|(_, video)| video.published > last_visit)
+                        .map(|(i, _)| i)
+                        .collect();
+                }
+            }
+            let named = app.loaded_data.subscriptions.fill_names(&videos);
+            if named {
+                utils::update_subscriptions_view(app);
+            }
+            app.loaded_data.subscription_feed = videos;
+
+            if !failed.is_empty() {
+                let channels: Vec<String> = failed.iter().map(|(author, _)| author.clone()).collect();
+                for (author, e) in failed {
+                    log::error!("Unable to load videos of {}: {}", author, e);
+                }
+                app.notify_error(format!("Unable to load videos of {}", channels.join(", ")));
+            }
+            if visited || named {
+                if let Err(e) = app.loaded_data.subscriptions.save() {
+                    log::error!("Unable to save subscriptions: {}", e);
+                }
+            }
+        },
+        Fetched::Account(result) => {
+            match result {
+                Ok((feed, playlists, channels)) => {
+                    if let Some(view) = app.view_list.get_mut(&ViewType::Home) {
+                        if let Some(window) = view.window_mut(WindowType::AccountFeed) {
+                            window.set_content(utils::video_to_text(feed.clone()));
+                        }
+                        if let Some(window) = view.window_mut(WindowType::AccountPlaylists) {
+                            window.set_content(utils::playlist_to_text(playlists.clone()));
+                        }
+                    }
+                    app.loaded_data.account_feed = feed;
+                    app.loaded_data.account_playlists = playlists;
+                    app.loaded_data.subscriptions.set_synced(channels);
+                    utils::update_subscriptions_view(app);
+                },
+                Err(e) => {
+                    stop_loading(app.view_list.get_mut(&ViewType::Home));
+                    app.notify_error(format!("Unable to load account: {}", e));
+                },
+            }
+        },
+        Fetched::Subscribed { author, author_id, subscribed, result } => {
+            match result {
+                Ok(_) => {
+                    if app.loaded_data.subscriptions.is_subscribed(&author_id) != subscribed {
+                        app.loaded_data.subscriptions.toggle(author.clone(), author_id);
+                    }
+                    utils::update_subscriptions_view(app);
+                    if subscribed {
+                        app.notify_info(format!("Subscribed to {}", author));
+                    }
+                    else {
+                        app.notify_info(format!("Unsubscribed from {}", author));
+                    }
+                },
+                Err(e) => app.notify_error(format!("Unable to update subscription to {}: {}", author, e)),
+            }
+        },
+        Fetched::Autoplay(id, result) => {
+            match result {
+                Ok(details) => {
+                    if !app.autoplay || app.audio_queue.has_next() {
+                        return
+                    }
+
+                    let history = &app.autoplay_history;
+                    let next = details.recommended_videos
+                        .into_iter()
+                        .find(|video| video.video_id != id && !history.contains(&video.video_id));
+                    match next {
+                        Some(video) => {
+                            app.autoplay_history.push_back(id);
+                            if app.autoplay_history.len() > AUTOPLAY_HISTORY_LEN {
+                                app.autoplay_history.pop_front();
+                            }
+                            let entry = QueueEntry::new(&video, Some(String::from("Autoplay")));
+                            append_to_queue(app, vec![entry], false);
+                        },
+                        None => app.notify_info(format!("No related video left to autoplay after {}", details.title)),
+                    }
+                },
+                Err(e) => app.notify_error(format!("Unable to find a related video to autoplay: {}", e)),
+            }
+        },
+        Fetched::Comments { video_id, sort, target, result } => {
+            let view = app.view_list
+                .values_mut()
+                .find_map(|view| view.find_media_view_mut(&video_id));
+            let window = match view.and_then(|view| view.window_mut(WindowType::VideoComments)) {
+                Some(window) => window,
+                None => return,
+            };
+
+            let comments = &mut app.loaded_data.comments;
+            let is_current = comments.video_id == video_id && comments.sort == sort;
+            let page = match result {
+                Ok(page) => page,
+                Err(e) => {
+                    if is_current || target == CommentTarget::Reload {
+                        window.loading = false;
+                    }
+                    app.notify_error(format!("Unable to load comments: {}", e));
+                    return
+                },
+            };
+
+            match target {
+                CommentTarget::Reload => {
+                    if comments.video_id == video_id && comments.sort != sort {
+                        log::info!("Discarding stale comments for {}", video_id);
+                        return
+                    }
+                    *comments = Comments::new(video_id, sort);
+                    comments.append_page(page);
+                    window.set_content(comments.to_text());
+                },
+                CommentTarget::NextPage(_) if is_current => {
+                    comments.append_page(page);
+                    window.update_content(comments.to_text());
+                },
+                CommentTarget::Replies(continuation) if is_current => {
+                    comments.insert_replies(&continuation, page);
+                    window.update_content(comments.to_text());
+                },
+                _ => window.loading = false,
+            }
+        },
+        Fetched::ProxyCheck(instance, result) => {
+            match result {
+                Ok(true) => {
+                    log::info!("Playing streams through {}", instance);
+                    app.player.enable_proxy(&instance);
+                },
+                Ok(false) => app.notify_error(format!("{} does not proxy streams, playing with youtube-dl", instance)),
+                Err(e) => app.notify_error(format!("Unable to check the proxy of {}, playing with youtube-dl: {}", instance, e)),
+            }
+        },
+        Fetched::PlaylistQueue { title, is_video, result } => {
+            match result {
+                Ok(videos) => {
+                    let entries = videos.iter()
+                        .map(|video| QueueEntry::new(video, Some(title.clone())))
+                        .collect();
+                    append_to_queue(app, entries, is_video);
+                },
+                Err(e) => app.notify_error(format!("Unable to queue {}: {}", title, e)),
+            }
+        },
+    }
+}
+
+/// Opens the command line with `text` already typed.
+fn open_command(app: &mut App, text: String) {
+    let prompt = match app.config.keys.command {
+        Key::Char(c) => c.to_string(),
+        _ => String::new(),
+    };
+    app.cmdline_focused = true;
+    app.input = LineEditor::new(InputMode::Command, &prompt);
+    app.input.set_text(text);
+    app.suggestions.clear();
+}
+
+/// Moves or removes the selected video of the opened local playlist.
+fn edit_playlist_videos(app: &mut App, key: Key) {
+    let name = match app.loaded_data.opened_playlist.clone() {
+        Some(name) => name,
+        None => return,
+    };
+    let selected = match utils::focused_window(app) {
+        Some(window) => window.selected,
+        None => return,
+    };
+    let playlist = match app.loaded_data.playlists.get_mut(&name) {
+        Some(playlist) if selected < playlist.videos.len() => playlist,
+        _ => return,
+    };
+
+    let new_selection = if key == app.config.keys.remove_from_playlist {
+        playlist.videos.remove(selected);
+        selected
+    }
+    else {
+        match playlist.move_video(selected, key == app.config.keys.move_video_up) {
+            Some(index) => index,
+            None => return,
+        }
+    };
+
+    utils::update_playlists_view(app);
+    if let Some(root_view) = app.view_list.get_mut(&ViewType::Playlists) {
+        if let Some(view) = root_view.get_current_view_mut() {
+            if let Some(window) = view.window_mut(WindowType::LocalPlaylistVideos) {
+                window.selected = new_selection.min(window_len(window).saturating_sub(1));
+            }
+        }
+    }
+    if let Err(e) = app.loaded_data.playlists.save() {
+        app.notify_error(format!("Unable to save playlists: {}", e));
+    }
+}
+
+/// Plays the selection, replacing the queue of the player. Remote playlists
+/// start playing once their videos are fetched.
+fn play_selection(app: &mut App, is_video: bool) {
+    if focused_window_type(app) == Some(WindowType::VideoFormats) {
+        play_format(app, is_video);
+        return
+    }
+    let selection = utils::focused_window(app).and_then(|window| {
+        let media = utils::get_media(window, &app.loaded_data)?;
+        let entries = utils::queue_entries(window, &app.loaded_data);
+        let playlist = utils::get_playlist(window, &app.loaded_data);
+        let video = utils::get_video(window, &app.loaded_data);
+        Some((media, entries, playlist, video))
+    });
+    let (media, entries, playlist, video) = match selection {
+        Some(selection) => selection,
+        None => return,
+    };
+
+    if let Some(playlist) = playlist {
+        // mpv drops its playlist along with the queue, so whatever gets
+        // queued before the videos arrive starts both afresh.
+        if is_video {
+            app.player.stop_video();
+        }
+        else {
+            app.player.stop_audio();
+        }
+        app.queue_mut(is_video).replace(vec![]);
+        fetch_playlist_queue(app, playlist, is_video);
+        return
+    }
+    if entries.is_empty() {
+        return
+    }
+
+    app.queue_mut(is_video).replace(entries);
+    let resume = video.as_ref().and_then(|video| app.loaded_data.watch_history.resume_position(&video.video_id));
+    match resume {
+        Some(start) => app.player.play_from(media.url(&app.api), is_video, Some(start)),
+        None if is_video => media.play_video(&mut app.player, &app.api),
+        None => media.play_audio(&mut app.player, &app.api),
+    }
+    if let Some(video) = video {
+        app.loaded_data.watch_history.record(&video);
+    }
+}
+
+/// Plays the stream selected in the formats tab of a video instead of the
+/// configured quality.
+fn play_format(app: &mut App, is_video: bool) {
+    let media_id = app.view_list
+        .get(&app.focused_view)
+        .and_then(|root_view| root_view.get_current_view())
+        .and_then(|view| view.media_id.clone());
+    let video = match app.loaded_data.formats_video.clone() {
+        Some(video) if media_id.as_ref() == Some(&video.video_id) => video,
+        _ => {
+            app.notify_error("Formats of this video are not loaded, open it again to play them");
+            return
+        },
+    };
+    let selected = utils::focused_window(app).map(|window| window.selected);
+    let format = match selected.and_then(|selected| app.loaded_data.video_formats.get(selected).cloned()) {
+        Some(format) => format,
+        None => return,
+    };
+    if format.is_video_only() && !is_video {
+        app.notify_error("This format has no audio, play it with video");
+        return
+    }
+    let audio_url = if format.is_video_only() {
+        best_audio(&app.loaded_data.video_formats, app.config.quality.audio_bitrate)
+            .map(|audio| stream_url(app, &audio.url))
+    }
+    else {
+        None
+    };
+
+    app.queue_mut(is_video).replace(vec![QueueEntry::new(&video, None)]);
+    let url = stream_url(app, &format.url);
+    app.player.play_stream(url, audio_url, is_video);
+    app.loaded_data.watch_history.record(&video);
+    app.notify_info(format!("Playing {} in {}", video.title, format.quality()));
+}
+
+/// Format urls are relative when the instance proxies the streams.
+fn stream_url(app: &App, url: &str) -> String {
+    if url.starts_with('/') {
+        format!("{}{}", app.api.instance(), url)
+    }
+    else {
+        url.to_owned()
+    }
+}
+
+/// Appends the selection to the queue of the player.
+fn queue_selection(app: &mut App, is_video: bool) {
+    let selection = utils::focused_window(app).map(|window| {
+        (utils::queue_entries(window, &app.loaded_data), utils::get_playlist(window, &app.loaded_data))
+    });
+    match selection {
+        Some((_, Some(playlist))) => fetch_playlist_queue(app, playlist, is_video),
+        Some((entries, None)) => append_to_queue(app, entries, is_video),
+        None => (),
+    }
+}
+
+fn fetch_playlist_queue(app: &mut App, playlist: Playlist, is_video: bool) {
+    let api = app.api.clone();
+    app.fetcher.spawn(async move {
+        let result = playlist.get_videos(&api, 1).await;
+        Fetched::PlaylistQueue {
+            title: playlist.title,
+            is_video,
+            result,
+        }
+    });
+}
+
+/// Loads entries into mpv after the ones already queued, starting playback
+/// if the queue was empty or the video player has exited since.
+fn append_to_queue(app: &mut App, entries: Vec<QueueEntry>, is_video: bool) {
+    let urls: Vec<String> = entries.iter().map(|entry| app.api.watch_url(&entry.video_id)).collect();
+    let player_alive = !is_video || app.player.has_video();
+    let play_first = app.queue_mut(is_video).enqueue(entries, player_alive);
+    for (i, url) in urls.into_iter().enumerate() {
+        if i == 0 && play_first {
+            app.player.play(url, is_video);
+        }
+        else if is_video {
+            app.player.queue_video(url);
+        }
+        else {
+            app.player.queue_audio(url);
+        }
+    }
+}
+
+/// Jumps to, moves, removes or clears entries of the focused queue, making
+/// the same change to the mpv playlist.
+fn edit_queue(app: &mut App, key: Key) {
+    let (window_type, selected) = match utils::focused_window(app) {
+        Some(window) => (window.window_type, window.selected),
+        None => return,
+    };
+    let is_video = window_type == WindowType::VideoQueue;
+
+    if key == app.config.keys.clear_queue {
+        app.queue_mut(is_video).clear();
+        app.player.playlist_clear(is_video);
+        utils::update_queue_view(app);
+        return
+    }
+    if selected >= app.queue_mut(is_video).entries.len() {
+        return
+    }
+
+    let new_selection = if key == app.config.keys.submit_entry {
+        app.queue_mut(is_video).current = Some(selected);
+        app.player.playlist_play_index(is_video, selected);
+        selected
+    }
+    else if key == app.config.keys.remove_from_playlist {
+        app.queue_mut(is_video).remove(selected);
+        app.player.playlist_remove(is_video, selected);
+        selected
+    }
+    else {
+        let up = key == app.config.keys.move_video_up;
+        match app.queue_mut(is_video).move_entry(selected, up) {
+            Some(index) => {
+                // mpv puts the track before the one at the given index.
+                let to = if up { index } else { index + 1 };
+                app.player.playlist_move(is_video, selected, to);
+                index
+            },
+            None => return,
+        }
+    };
+
+    utils::update_queue_view(app);
+    if let Some(view) = app.view_list.get_mut(&ViewType::Queue) {
+        if let Some(window) = view.window_mut(window_type) {
+            window.selected = new_selection.min(window_len(window).saturating_sub(1));
+        }
+    }
+}
+
+fn is_queue_window(window_type: Option<WindowType>) -> bool {
+    window_type == Some(WindowType::AudioQueue) || window_type == Some(WindowType::VideoQueue)
+}
+
+fn is_local_playlist_window(window_type: Option<WindowType>) -> bool {
+    window_type == Some(WindowType::LocalPlaylists) || window_type == Some(WindowType::LocalPlaylistVideos)
+}
+
+fn window_len(window: &Window) -> usize {
+    match &window.content {
+        ContentType::ListContent(vec) => vec.len(),
+        ContentType::TextContent(vec) => vec.len(),
+        ContentType::MediaContent(vec) => vec.read().unwrap().len(),
+    }
+}
+
+/// Shows the formats of the selected video to play one of them, or of the
+/// opened video.
+fn pick_format(app: &mut App) {
+    let video = utils::focused_window(app).and_then(|window| utils::get_video(window, &app.loaded_data));
+    let new_view = video.and_then(|video| video.open(&app.api, &app.fetcher));
+    let root_view = match app.view_list.get_mut(&app.focused_view) {
+        Some(root_view) => root_view,
+        None => return,
+    };
+    if let Some(new_view) = new_view {
+        root_view.view_stack.push(new_view);
+    }
+    let view = match root_view.get_current_view_mut() {
+        Some(view) => view,
+        None => return,
+    };
+    match view.root_windows.iter().position(|window| window.window_type == WindowType::VideoFormats) {
+        Some(tab) => view.tabs.selected = tab,
+        None => app.notify_error("Selection is not a video"),
+    }
+}
+
+fn focused_window_type(app: &App) -> Option<WindowType> {
+    let root_view = app.view_list.get(&app.focused_view)?;
+    let view = root_view.get_current_view()?;
+    view.root_windows
+        .get(view.tabs.selected)
+        .map(|window| window.window_type)
+}
+
+fn stop_loading(view: Option<&mut View>) {
+    if let Some(view) = view {
+        for window in view.root_windows.iter_mut() {
+            window.loading = false;
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use serde::{
+    Serialize,
+    Deserialize,
+};
+use failure::Error;
+use crate::store;
+
+const SEARCH_HISTORY_FILE: &str = "search_history.json";
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct SearchEntry {
+    pub query: String,
+    pub timestamp: i64,
+}
+
+/// Reverse incremental search state, started with Ctrl-R.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReverseSearch {
+    pub pattern: String,
+    pub index: Option<usize>,
+}
+
+/// Submitted searches, oldest first, along with the state of recalling them
+/// from the command line.
+#[derive(Debug)]
+pub struct SearchHistory {
+    entries: Vec<SearchEntry>,
+    max_size: usize,
+    position: Option<usize>,
+    draft: String,
+    pub reverse_search: Option<ReverseSearch>,
+}
+
+impl SearchHistory {
+    pub fn load(max_size: usize) -> Self {
+        let mut history = SearchHistory {
+            entries: store::load(SEARCH_HISTORY_FILE),
+            max_size,
+            ..SearchHistory::default()
+        };
+        history.truncate();
+        history
+    }
+
+    pub fn save(&self) -> Result<(), Error> {
+        store::save(SEARCH_HISTORY_FILE, &self.entries)
+    }
+
+    /// Records a submitted search, moving it to the front if it was already
+    /// in the history.
+    pub fn push(&mut self, query: &str) {
+        self.reset();
+        if query.trim().is_empty() || self.max_size == 0 {
+            return
+        }
+
+        self.entries.retain(|entry| entry.query != query);
+        self.entries.push(SearchEntry {
+            query: query.to_owned(),
+            timestamp: chrono::Utc::now().timestamp(),
+        });
+        self.truncate();
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.reset();
+    }
+
+    /// Stops recalling, so the next Up starts from the newest entry again.
+    pub fn reset(&mut self) {
+        self.position = None;
+        self.draft.clear();
+        self.reverse_search = None;
+    }
+
+    /// Steps to an older entry. `current` is kept as a draft to come back to.
+    pub fn prev(&mut self, current: &str) -> Option<&str> {
+        let position = match self.position {
+            Some(0) => return None,
+            Some(position) => position - 1,
+            None if self.entries.is_empty() => return None,
+            None => {
+                self.draft = current.to_owned();
+                self.entries.len() - 1
+            },
+        };
+        self.position = Some(position);
+        Some(&self.entries[position].query)
+    }
+
+    /// Steps to a newer entry, ending with the draft.
+    pub fn next(&mut self) -> Option<&str> {
+        let position = self.position?;
+        if position + 1 < self.entries.len() {
+            self.position = Some(position + 1);
+            Some(&self.entries[position + 1].query)
+        }
+        else {
+            self.position = None;
+            Some(&self.draft)
+        }
+    }
+
+    pub fn start_reverse_search(&mut self) {
+        self.reverse_search = Some(ReverseSearch {
+            pattern: String::new(),
+            index: None,
+        });
+    }
+
+    pub fn reverse_search_push(&mut self, c: char) {
+        if let Some(search) = &mut self.reverse_search {
+            search.pattern.push(c);
+        }
+        self.find_match(self.entries.len());
+    }
+
+    pub fn reverse_search_pop(&mut self) {
+        if let Some(search) = &mut self.reverse_search {
+            search.pattern.pop();
+        }
+        self.find_match(self.entries.len());
+    }
+
+    /// Moves on to the next older match, keeping the current one if there is
+    /// none.
+    pub fn reverse_search_next(&mut self) {
+        let current = self.reverse_search.as_ref().and_then(|search| search.index);
+        self.find_match(current.unwrap_or(self.entries.len()));
+        if let Some(search) = &mut self.reverse_search {
+            if search.index.is_none() {
+                search.index = current;
+            }
+        }
+    }
+
+    pub fn reverse_search_match(&self) -> Option<&str> {
+        let index = self.reverse_search.as_ref()?.index?;
+        self.entries.get(index).map(|entry| entry.query.as_str())
+    }
+
+    /// Finds the newest entry before `before` containing the pattern.
+    fn find_match(&mut self, before: usize) {
+        let entries = &self.entries;
+        if let Some(search) = &mut self.reverse_search {
+            search.index = entries[..before.min(entries.len())]
+                .iter()
+                .rposition(|entry| entry.query.contains(search.pattern.as_str()));
+        }
+    }
+
+    fn truncate(&mut self) {
+        if self.entries.len() > self.max_size {
+            let excess = self.entries.len() - self.max_size;
+            self.entries.drain(..excess);
+        }
+    }
+}
+
+impl Default for SearchHistory {
+    fn default() -> Self {
+        SearchHistory {
+            entries: vec![],
+            max_size: 0,
+            position: None,
+            draft: String::new(),
+            reverse_search: None,
+        }
+    }
+}
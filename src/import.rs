@@ -0,0 +1,255 @@
+use serde::{
+    Serialize,
+    Deserialize,
+};
+use serde_json::Value;
+use failure::{
+    Error,
+    format_err,
+};
+use crate::subscriptions::Subscription;
+use std::{
+    fs,
+    path::{
+        Path,
+        PathBuf,
+    },
+};
+
+#[derive(Debug, Serialize, Deserialize)]
+struct NewPipeExport {
+    app_version: String,
+    app_version_int: u32,
+    subscriptions: Vec<NewPipeSubscription>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct NewPipeSubscription {
+    service_id: u32,
+    url: String,
+    name: String,
+}
+
+/// Reads subscriptions from a YouTube Takeout CSV/JSON export, a NewPipe
+/// export, or an Invidious OPML/JSON export. The format is detected from the
+/// content rather than the file name.
+pub fn read_subscriptions(path: &Path) -> Result<Vec<Subscription>, Error> {
+    let content = fs::read_to_string(path)?;
+    let content = content.trim_start_matches('\u{feff}').trim();
+
+    let subscriptions = if content.starts_with('<') {
+        parse_opml(content)
+    }
+    else if content.starts_with('{') || content.starts_with('[') {
+        parse_json(&serde_json::from_str(content)?)?
+    }
+    else {
+        parse_takeout_csv(content)?
+    };
+
+    if subscriptions.is_empty() {
+        return Err(format_err!("No subscriptions found in {}", path.display()))
+    }
+    Ok(subscriptions)
+}
+
+pub fn write_opml(path: &Path, subscriptions: &[Subscription]) -> Result<(), Error> {
+    let mut opml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    opml.push_str("<opml version=\"1.1\">\n  <body>\n");
+    opml.push_str("    <outline text=\"YouTube Subscriptions\" title=\"YouTube Subscriptions\">\n");
+    for subscription in subscriptions {
+        let author = escape_xml(&subscription.author);
+        opml.push_str(&format!(
+            "      <outline text=\"{}\" title=\"{}\" type=\"rss\" xmlUrl=\"https://www.youtube.com/feeds/videos.xml?channel_id={}\"/>\n",
+            author,
+            author,
+            escape_xml(&subscription.author_id),
+        ));
+    }
+    opml.push_str("    </outline>\n  </body>\n</opml>\n");
+    fs::write(path, opml)?;
+    Ok(())
+}
+
+pub fn write_newpipe(path: &Path, subscriptions: &[Subscription]) -> Result<(), Error> {
+    let export = NewPipeExport {
+        app_version: String::from("0.19.0"),
+        app_version_int: 900,
+        subscriptions: subscriptions
+            .iter()
+            .map(|subscription| NewPipeSubscription {
+                service_id: 0,
+                url: format!("https://www.youtube.com/channel/{}", subscription.author_id),
+                name: subscription.author.clone(),
+            })
+            .collect(),
+    };
+    fs::write(path, serde_json::to_string_pretty(&export)?)?;
+    Ok(())
+}
+
+/// Expands a leading `~` so paths can be typed as in a shell.
+pub fn expand_path(path: &str) -> PathBuf {
+    if path == "~" || path.starts_with("~/") {
+        if let Some(home) = dirs::home_dir() {
+            return home.join(path.trim_start_matches('~').trim_start_matches('/'))
+        }
+    }
+    PathBuf::from(path)
+}
+
+fn parse_json(json: &Value) -> Result<Vec<Subscription>, Error> {
+    // NewPipe: {"subscriptions": [{"url": ..., "name": ...}]}
+    // Invidious: {"subscriptions": ["UC..."]}
+    if let Some(entries) = json["subscriptions"].as_array() {
+        let subscriptions = entries.iter().filter_map(|entry| {
+            match entry {
+                Value::String(author_id) => Some(Subscription {
+                    author: author_id.clone(),
+                    author_id: author_id.clone(),
+                }),
+                _ => {
+                    let author_id = channel_id_from_url(entry["url"].as_str()?)?;
+                    let author = entry["name"].as_str().unwrap_or(&author_id).to_owned();
+                    Some(Subscription {
+                        author,
+                        author_id,
+                    })
+                },
+            }
+        });
+        return Ok(subscriptions.collect())
+    }
+
+    // YouTube Takeout: [{"snippet": {"title": ..., "resourceId": {"channelId": ...}}}]
+    if let Some(entries) = json.as_array() {
+        let subscriptions = entries.iter().filter_map(|entry| {
+            let snippet = &entry["snippet"];
+            let author_id = snippet["resourceId"]["channelId"].as_str()?.to_owned();
+            let author = snippet["title"].as_str().unwrap_or(&author_id).to_owned();
+            Some(Subscription {
+                author,
+                author_id,
+            })
+        });
+        return Ok(subscriptions.collect())
+    }
+
+    Err(format_err!("Unrecognized subscription export"))
+}
+
+/// YouTube Takeout `subscriptions.csv`: `Channel Id,Channel Url,Channel Title`.
+fn parse_takeout_csv(content: &str) -> Result<Vec<Subscription>, Error> {
+    let mut lines = content.lines().filter(|line| !line.trim().is_empty());
+    let header = lines.next().map(split_csv_line).unwrap_or_default();
+    let column = |name: &str| header.iter().position(|column| column.trim().eq_ignore_ascii_case(name));
+    let (id_column, title_column) = match (column("Channel Id"), column("Channel Title")) {
+        (Some(id_column), Some(title_column)) => (id_column, title_column),
+        _ => return Err(format_err!("Unrecognized CSV header, expected Channel Id and Channel Title columns")),
+    };
+
+    let subscriptions = lines.filter_map(|line| {
+        let fields = split_csv_line(line);
+        let author_id = fields.get(id_column)?.trim().to_owned();
+        if author_id.is_empty() {
+            return None
+        }
+        let author = fields.get(title_column)
+            .map(|title| title.trim().to_owned())
+            .unwrap_or_else(|| author_id.clone());
+        Some(Subscription {
+            author,
+            author_id,
+        })
+    });
+    Ok(subscriptions.collect())
+}
+
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = vec![];
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            },
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+/// Collects the channels of every `<outline>` with a YouTube feed url.
+fn parse_opml(content: &str) -> Vec<Subscription> {
+    content
+        .split("<outline")
+        .skip(1)
+        .filter_map(|outline| {
+            let tag = &outline[..outline.find('>')?];
+            let author_id = channel_id_from_url(&xml_attribute(tag, "xmlUrl")?)?;
+            let author = xml_attribute(tag, "title")
+                .or_else(|| xml_attribute(tag, "text"))
+                .unwrap_or_else(|| author_id.clone());
+            Some(Subscription {
+                author,
+                author_id,
+            })
+        })
+        .collect()
+}
+
+fn xml_attribute(tag: &str, name: &str) -> Option<String> {
+    let pattern = format!(" {}=", name);
+    let start = tag.find(&pattern)? + pattern.len();
+    let rest = &tag[start..];
+    let quote = rest.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+    let value = &rest[1..];
+    let end = value.find(quote)?;
+    Some(unescape_xml(&value[..end]))
+}
+
+fn unescape_xml(value: &str) -> String {
+    value
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Extracts the channel id from `.../channel/UC...` or `...?channel_id=UC...`.
+fn channel_id_from_url(url: &str) -> Option<String> {
+    let start = if let Some(i) = url.find("/channel/") {
+        i + "/channel/".len()
+    }
+    else {
+        url.find("channel_id=")? + "channel_id=".len()
+    };
+    let id: String = url[start..]
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+        .collect();
+    if id.is_empty() {
+        None
+    }
+    else {
+        Some(id)
+    }
+}
@@ -0,0 +1,184 @@
+use std::time::{
+    Duration,
+    Instant,
+};
+
+/// Time the input has to stay unchanged before suggestions are requested.
+pub const SUGGESTION_DELAY: Duration = Duration::from_millis(300);
+pub const MAX_SUGGESTIONS: usize = 8;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum InputMode {
+    Search,
+    Command,
+}
+
+/// Contents of the command line. The prompt is the key that opened it and
+/// cannot be edited; `cursor` is a byte offset into `text` that always sits on
+/// a char boundary.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LineEditor {
+    pub mode: InputMode,
+    pub prompt: String,
+    text: String,
+    cursor: usize,
+}
+
+impl LineEditor {
+    pub fn new(mode: InputMode, prompt: &str) -> Self {
+        LineEditor {
+            mode,
+            prompt: prompt.to_owned(),
+            ..LineEditor::default()
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    /// Text before the cursor, the char under it and the text after it.
+    pub fn split_at_cursor(&self) -> (&str, Option<char>, &str) {
+        let (before, rest) = self.text.split_at(self.cursor);
+        let mut chars = rest.chars();
+        let current = chars.next();
+        (before, current, chars.as_str())
+    }
+
+    pub fn set_text(&mut self, text: String) {
+        self.cursor = text.len();
+        self.text = text;
+    }
+
+    pub fn insert(&mut self, c: char) {
+        self.text.insert(self.cursor, c);
+        self.cursor += c.len_utf8();
+    }
+
+    pub fn delete_before(&mut self) {
+        if let Some(c) = self.text[..self.cursor].chars().next_back() {
+            self.cursor -= c.len_utf8();
+            self.text.remove(self.cursor);
+        }
+    }
+
+    pub fn delete_at(&mut self) {
+        if self.cursor < self.text.len() {
+            self.text.remove(self.cursor);
+        }
+    }
+
+    pub fn move_left(&mut self) {
+        if let Some(c) = self.text[..self.cursor].chars().next_back() {
+            self.cursor -= c.len_utf8();
+        }
+    }
+
+    pub fn move_right(&mut self) {
+        if let Some(c) = self.text[self.cursor..].chars().next() {
+            self.cursor += c.len_utf8();
+        }
+    }
+
+    pub fn move_home(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn move_end(&mut self) {
+        self.cursor = self.text.len();
+    }
+
+    /// Deletes the word before the cursor along with the whitespace after it.
+    pub fn delete_word(&mut self) {
+        let before = self.text[..self.cursor].trim_end();
+        let start = before
+            .rfind(char::is_whitespace)
+            .map(|i| i + before[i..].chars().next().map(char::len_utf8).unwrap_or(1))
+            .unwrap_or(0);
+        self.text.replace_range(start..self.cursor, "");
+        self.cursor = start;
+    }
+
+    pub fn delete_to_start(&mut self) {
+        self.text.replace_range(..self.cursor, "");
+        self.cursor = 0;
+    }
+}
+
+impl Default for LineEditor {
+    fn default() -> Self {
+        LineEditor {
+            mode: InputMode::Search,
+            prompt: String::new(),
+            text: String::new(),
+            cursor: 0,
+        }
+    }
+}
+
+/// Search suggestions shown above the command line while typing.
+#[derive(Clone, Debug)]
+pub struct Suggestions {
+    pub items: Vec<String>,
+    pub selected: Option<usize>,
+    pub requested: String,
+    pub changed: Option<Instant>,
+}
+
+impl Suggestions {
+    pub fn clear(&mut self) {
+        *self = Suggestions::default();
+    }
+
+    /// Marks the input as edited, restarting the debounce delay.
+    pub fn input_changed(&mut self) {
+        self.changed = Some(Instant::now());
+        self.selected = None;
+    }
+
+    pub fn is_due(&self) -> bool {
+        self.changed
+            .map(|changed| changed.elapsed() >= SUGGESTION_DELAY)
+            .unwrap_or(false)
+    }
+
+    pub fn select_next(&mut self) -> Option<&str> {
+        if self.items.is_empty() {
+            return None
+        }
+        let next = match self.selected {
+            Some(selected) if selected + 1 < self.items.len() => selected + 1,
+            Some(_) => 0,
+            None => 0,
+        };
+        self.selected = Some(next);
+        self.items.get(next).map(String::as_str)
+    }
+
+    pub fn select_prev(&mut self) -> Option<&str> {
+        if self.items.is_empty() {
+            return None
+        }
+        let prev = match self.selected {
+            Some(selected) if selected > 0 => selected - 1,
+            _ => self.items.len() - 1,
+        };
+        self.selected = Some(prev);
+        self.items.get(prev).map(String::as_str)
+    }
+}
+
+impl Default for Suggestions {
+    fn default() -> Self {
+        Suggestions {
+            items: vec![],
+            selected: None,
+            requested: String::new(),
+            changed: None,
+        }
+    }
+}
@@ -1,92 +1,472 @@
-use failure::Error as AnyError;
 use reqwest::{
     Client,
-    Error,
+    Method,
+    Response,
+    StatusCode,
+    Url,
 };
+use serde::{
+    Serialize,
+    Deserialize,
+    de::DeserializeOwned,
+};
+use serde_json::Value;
 use crate::{
     media::{
-        video::Video,
-        channel::Channel,
+        video::{
+            Video,
+            VideoDetails,
+        },
+        comment::{
+            CommentPage,
+            CommentSort,
+        },
         playlist::Playlist,
     },
-    data::Search,
+    data::{
+        Search,
+        SearchFilters,
+    },
+    auth::Credentials,
+    subscriptions::Subscription,
 };
-use std::sync::{
-    Arc,
-    RwLock,
+use std::{
+    fmt,
+    collections::VecDeque,
+    sync::{
+        Arc,
+        RwLock,
+    },
+    time::{
+        Duration,
+        Instant,
+    },
 };
 
-const SEARCH_URL: &str = "https://www.invidio.us/api/v1/search";
-const TRENDING_URL: &str = "https://www.invidio.us/api/v1/trending";
-const POPULAR_URL: &str = "https://www.invidio.us/api/v1/popular";
-//const TOP_URL: &str = "https://www.invidio.us/api/v1/top";
+/// Feed, playlists and subscriptions of the logged in account.
+pub type Account = (Vec<Video>, Vec<Playlist>, Vec<Subscription>);
+
+const FAILURE_WINDOW: Duration = Duration::from_secs(300);
+const MAX_RECENT_FAILURES: usize = 3;
+/// Video requested to check whether an instance proxies streams.
+const PROXY_CHECK_VIDEO: &str = "jNQXAC9IVRw";
+
+/// Health of a single instance, tracked for the length of the session.
+#[derive(Clone, Debug)]
+pub struct InstanceHealth {
+    pub url: String,
+    pub latency: Option<Duration>,
+    pub successes: u32,
+    pub failures: u32,
+    recent_failures: VecDeque<Instant>,
+}
+
+impl InstanceHealth {
+    fn new(url: String) -> Self {
+        InstanceHealth {
+            url,
+            latency: None,
+            successes: 0,
+            failures: 0,
+            recent_failures: VecDeque::new(),
+        }
+    }
 
+    pub fn recent_failures(&self) -> usize {
+        self.recent_failures
+            .iter()
+            .filter(|time| time.elapsed() < FAILURE_WINDOW)
+            .count()
+    }
 
-pub async fn load_home(client: &Client) -> Result<(Vec<Video>, Vec<Video>), reqwest::Error> {
-    let trending_fut = invidious_videos(vec![], client, TRENDING_URL);
-    let popular_fut = invidious_videos(vec![], client, POPULAR_URL);
-    //let top_fut = home_videos(client, TOP_URL);
+    fn record_success(&mut self, latency: Duration) {
+        self.successes += 1;
+        self.latency = Some(latency);
+    }
 
-    futures::try_join!(trending_fut, popular_fut)//, top_fut)
+    fn record_failure(&mut self) {
+        self.failures += 1;
+        self.recent_failures.push_back(Instant::now());
+        while let Some(time) = self.recent_failures.front() {
+            if time.elapsed() < FAILURE_WINDOW {
+                break;
+            }
+            self.recent_failures.pop_front();
+        }
+    }
 }
 
-pub async fn search(query: String, client: &Client) -> Result<Search, AnyError> {
-    let params = vec![
-        ("q", query.as_str()),
-        ("page", "1"),
-        ("type", "video"),
-        ("sort_by", "relevance"),
-    ];
-    let videos_fut = invidious_videos(params, &client, SEARCH_URL);
-    
-    let params = vec![
-        ("q", query.as_str()),
-        ("page", "1"),
-        ("type", "playlist"),
-        ("sort_by", "relevance"),
-    ];
-    let playlists_fut = invidious_playlists(params, &client, SEARCH_URL);
-    
-    let params = vec![
-        ("q", query.as_str()),
-        ("page", "1"),
-        ("type", "channel"),
-        ("sort_by", "relevance"),
-    ];
-    let channels_fut = invidious_channels(params, &client, SEARCH_URL);
-
-    let (videos, playlists, channels) = futures::try_join!(videos_fut, playlists_fut, channels_fut)?;
-    
-    Ok(Search {
-        query,
-        videos: (Arc::new(RwLock::new(videos)), 1),
-        playlists: (Arc::new(RwLock::new(playlists)), 1),
-        channels: (Arc::new(RwLock::new(channels)), 1),
-    })
+#[derive(Debug)]
+struct InstancePool {
+    instances: Vec<InstanceHealth>,
+    active: usize,
 }
 
-pub async fn invidious_videos(params: Vec<(&str, &str)>, client: &Client, url: &str) -> Result<Vec<Video>, Error> {
-    let rsp = client.get(url)
-        .query(&params)
-        .send()
-        .await?;
-    rsp.json().await
+impl InstancePool {
+    /// Indices to try for the next request: the active instance first, then
+    /// the rest in configured order, with instances that keep failing last.
+    fn order(&self) -> Vec<usize> {
+        let len = self.instances.len();
+        let (mut healthy, unhealthy): (Vec<usize>, Vec<usize>) = (0..len)
+            .map(|i| (self.active + i) % len)
+            .partition(|&i| self.instances[i].recent_failures() < MAX_RECENT_FAILURES);
+        healthy.extend(unhealthy);
+        healthy
+    }
 }
 
-pub async fn invidious_playlists(params: Vec<(&str, &str)>, client: &Client, url: &str) -> Result<Vec<Playlist>, Error> {
-    let rsp = client.get(url)
-        .query(&params)
-        .send()
-        .await?;
-    rsp.json().await
+#[derive(Debug)]
+pub enum ApiError {
+    Network(reqwest::Error),
+    Status(u16, String),
+    Decode(serde_json::Error),
+    InstanceDisabled(String),
+    RateLimited,
+    NotLoggedIn,
 }
 
-pub async fn invidious_channels(params: Vec<(&str, &str)>, client: &Client, url: &str) -> Result<Vec<Channel>, Error> {
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ApiError::Network(e) => write!(f, "Network error: {}", e),
+            ApiError::Status(status, message) => write!(f, "Instance returned HTTP {}: {}", status, message),
+            ApiError::Decode(e) => write!(f, "Unable to decode response: {}", e),
+            ApiError::InstanceDisabled(message) => write!(f, "Instance has disabled this endpoint: {}", message),
+            ApiError::RateLimited => write!(f, "Rate limited by instance"),
+            ApiError::NotLoggedIn => write!(f, "Not logged in"),
+        }
+    }
+}
+
+impl std::error::Error for ApiError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ApiError::Network(e) => Some(e),
+            ApiError::Decode(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl ApiError {
+    /// Whether the request is worth retrying on the next instance, as opposed
+    /// to errors that would fail the same way anywhere.
+    fn should_failover(&self) -> bool {
+        match self {
+            ApiError::Network(_) => true,
+            ApiError::Status(status, _) => *status >= 500,
+            ApiError::Decode(_) => true,
+            ApiError::InstanceDisabled(_) => true,
+            ApiError::RateLimited => true,
+            ApiError::NotLoggedIn => false,
+        }
+    }
+}
+
+impl From<reqwest::Error> for ApiError {
+    fn from(e: reqwest::Error) -> Self {
+        ApiError::Network(e)
+    }
+}
 
-    let rsp = client.get(url)
-        .query(&params)
-        .send()
-        .await?;
-    rsp.json().await
+impl From<serde_json::Error> for ApiError {
+    fn from(e: serde_json::Error) -> Self {
+        ApiError::Decode(e)
+    }
 }
 
+#[derive(Deserialize)]
+struct SearchSuggestions {
+    suggestions: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(default)]
+struct Feed {
+    notifications: Vec<Video>,
+    videos: Vec<Video>,
+}
+
+impl Default for Feed {
+    fn default() -> Self {
+        Feed {
+            notifications: vec![],
+            videos: vec![],
+        }
+    }
+}
+
+/// Client for the configured Invidious instances. Every API request and
+/// every generated watch/playlist link goes through the active instance,
+/// falling back to the next one when it stops responding.
+#[derive(Clone, Debug)]
+pub struct Invidious {
+    client: Client,
+    pool: Arc<RwLock<InstancePool>>,
+    credentials: Arc<RwLock<Option<Credentials>>>,
+}
+
+impl Invidious {
+    pub fn new(instances: Vec<String>, timeout: Duration) -> Self {
+        let client = Client::builder()
+            .timeout(timeout)
+            .build()
+            .unwrap_or_else(|e| {
+                log::error!("Unable to build client with timeout: {}", e);
+                Client::new()
+            });
+        let instances = instances.into_iter().map(InstanceHealth::new).collect();
+
+        Invidious {
+            client,
+            pool: Arc::new(RwLock::new(InstancePool {
+                instances,
+                active: 0,
+            })),
+            credentials: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    pub fn set_credentials(&self, credentials: Option<Credentials>) {
+        *self.credentials.write().unwrap() = credentials;
+    }
+
+    pub fn is_logged_in(&self) -> bool {
+        self.credentials.read().unwrap().is_some()
+    }
+
+    pub fn instance(&self) -> String {
+        let pool = self.pool.read().unwrap();
+        pool.instances[pool.active].url.clone()
+    }
+
+    pub fn active_health(&self) -> InstanceHealth {
+        let pool = self.pool.read().unwrap();
+        pool.instances[pool.active].clone()
+    }
+
+    pub fn api_url(&self, endpoint: &str) -> String {
+        format!("{}/api/v1/{}", self.instance(), endpoint)
+    }
+
+    pub fn watch_url(&self, video_id: &str) -> String {
+        format!("{}/watch?v={}", self.instance(), video_id)
+    }
+
+    pub fn playlist_url(&self, playlist_id: &str) -> String {
+        format!("{}/playlist?list={}", self.instance(), playlist_id)
+    }
+
+    pub async fn get_json<T, P>(&self, endpoint: &str, params: &P) -> Result<T, ApiError>
+    where
+        T: DeserializeOwned,
+        P: Serialize + ?Sized,
+    {
+        let order = self.pool.read().unwrap().order();
+        let mut last_error = None;
+
+        for i in order {
+            let instance = self.pool.read().unwrap().instances[i].url.clone();
+            let start = Instant::now();
+            match self.request(&instance, endpoint, params).await {
+                Ok(json) => {
+                    let mut pool = self.pool.write().unwrap();
+                    pool.instances[i].record_success(start.elapsed());
+                    if pool.active != i {
+                        log::info!("Switched active instance to {}", instance);
+                        pool.active = i;
+                    }
+                    drop(pool);
+                    return Ok(serde_json::from_value(json)?)
+                },
+                Err(e) if e.should_failover() => {
+                    log::error!("Request to {} failed, trying next instance: {}", instance, e);
+                    self.pool.write().unwrap().instances[i].record_failure();
+                    last_error = Some(e);
+                },
+                Err(e) => return Err(e),
+            }
+        }
+
+        Err(last_error.unwrap_or_else(|| ApiError::InstanceDisabled(String::from("no instances configured"))))
+    }
+
+    async fn request<P>(&self, instance: &str, endpoint: &str, params: &P) -> Result<Value, ApiError>
+    where
+        P: Serialize + ?Sized,
+    {
+        let url = format!("{}/api/v1/{}", instance, endpoint);
+        let rsp = self.client.get(&url).query(params).send().await?;
+        Self::read_json(rsp).await
+    }
+
+    async fn read_json(rsp: Response) -> Result<Value, ApiError> {
+        let status = rsp.status();
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            return Err(ApiError::RateLimited)
+        }
+
+        let body = rsp.text().await?;
+        let json: Value = match serde_json::from_str(&body) {
+            Ok(json) => json,
+            Err(_) if !status.is_success() => return Err(ApiError::Status(status.as_u16(), body)),
+            Err(e) => return Err(e.into()),
+        };
+
+        if status.is_success() {
+            return Ok(json)
+        }
+
+        let message = json["error"].as_str().unwrap_or_default().to_owned();
+        if message.contains("disabled") {
+            Err(ApiError::InstanceDisabled(message))
+        }
+        else {
+            Err(ApiError::Status(status.as_u16(), message))
+        }
+    }
+
+    pub async fn load_home(&self) -> Result<(Vec<Video>, Vec<Video>), ApiError> {
+        let trending_fut = self.videos(vec![], "trending");
+        let popular_fut = self.videos(vec![], "popular");
+        //let top_fut = self.videos(vec![], "top");
+
+        futures::try_join!(trending_fut, popular_fut)//, top_fut)
+    }
+
+    pub async fn search(&self, query: String, filters: SearchFilters) -> Result<Search, ApiError> {
+        let videos_fut = self.search_page(&query, &filters, "video", 1);
+        let playlists_fut = self.search_page(&query, &filters, "playlist", 1);
+        let channels_fut = self.search_page(&query, &filters, "channel", 1);
+
+        let (videos, playlists, channels) = futures::try_join!(videos_fut, playlists_fut, channels_fut)?;
+
+        Ok(Search {
+            query,
+            filters,
+            videos: (Arc::new(RwLock::new(videos)), 1),
+            playlists: (Arc::new(RwLock::new(playlists)), 1),
+            channels: (Arc::new(RwLock::new(channels)), 1),
+        })
+    }
+
+    pub async fn search_suggestions(&self, query: &str) -> Result<Vec<String>, ApiError> {
+        let suggestions: SearchSuggestions = self.get_json("search/suggestions", &[("q", query)]).await?;
+        Ok(suggestions.suggestions)
+    }
+
+    /// A page of search results of one type, or nothing when the filters
+    /// exclude that type.
+    pub async fn search_page<T>(&self, query: &str, filters: &SearchFilters, search_type: &str, page: u32) -> Result<Vec<T>, ApiError>
+    where
+        T: DeserializeOwned,
+    {
+        if !filters.includes(search_type) {
+            return Ok(vec![])
+        }
+        self.get_json("search", &filters.params(query, search_type, page)).await
+    }
+
+    pub async fn video(&self, video_id: &str) -> Result<VideoDetails, ApiError> {
+        let endpoint = format!("videos/{}", video_id);
+        self.get_json(&endpoint, &[] as &[(&str, &str)]).await
+    }
+
+    /// Top level comments when `continuation` is `None`, otherwise the page
+    /// the token points to, which may be a reply thread.
+    pub async fn comments(&self, video_id: &str, sort: CommentSort, continuation: Option<&str>) -> Result<CommentPage, ApiError> {
+        let endpoint = format!("comments/{}", video_id);
+        let mut params = vec![("sort_by", sort.as_param())];
+        if let Some(continuation) = continuation {
+            params.push(("continuation", continuation));
+        }
+        self.get_json(&endpoint, &params).await
+    }
+
+    /// A page of a playlist. Private playlists of the account, whose ids start
+    /// with `IV`, are only shown to it, so they are retried through it.
+    pub async fn playlist_page(&self, playlist_id: &str, page: u32) -> Result<Value, ApiError> {
+        let endpoint = format!("playlists/{}", playlist_id);
+        match self.get_json(&endpoint, &[("page", page)]).await {
+            Err(ApiError::Status(status, message)) if self.is_logged_in() && playlist_id.starts_with("IV") => {
+                log::debug!("Playlist {} not shown publicly ({}: {}), trying the account", playlist_id, status, message);
+                self.auth_request(Method::GET, &format!("{}?page={}", endpoint, page)).await
+            },
+            res => res,
+        }
+    }
+
+    pub async fn videos(&self, params: Vec<(&str, &str)>, endpoint: &str) -> Result<Vec<Video>, ApiError> {
+        self.get_json(endpoint, &params).await
+    }
+
+    /// Sends an authenticated request to the instance the account belongs
+    /// to. There is no failover, since the token is only valid there.
+    async fn auth_request(&self, method: Method, endpoint: &str) -> Result<Value, ApiError> {
+        let credentials = self.credentials.read().unwrap().clone();
+        let credentials = credentials.ok_or(ApiError::NotLoggedIn)?;
+        let url = format!("{}/api/v1/auth/{}", credentials.instance, endpoint);
+        let rsp = self.client.request(method, &url)
+            .bearer_auth(credentials.token)
+            .send()
+            .await?;
+
+        if rsp.status() == StatusCode::NO_CONTENT {
+            return Ok(Value::Null)
+        }
+        Self::read_json(rsp).await
+    }
+
+    /// Latest videos of the account's subscriptions, unwatched notifications
+    /// first.
+    pub async fn feed(&self) -> Result<Vec<Video>, ApiError> {
+        let feed: Feed = serde_json::from_value(self.auth_request(Method::GET, "feed").await?)?;
+        let mut videos = feed.notifications;
+        videos.extend(feed.videos);
+        Ok(videos)
+    }
+
+    pub async fn load_account(&self) -> Result<Account, ApiError> {
+        futures::try_join!(self.feed(), self.account_playlists(), self.account_subscriptions())
+    }
+
+    pub async fn account_playlists(&self) -> Result<Vec<Playlist>, ApiError> {
+        Ok(serde_json::from_value(self.auth_request(Method::GET, "playlists").await?)?)
+    }
+
+    pub async fn account_subscriptions(&self) -> Result<Vec<Subscription>, ApiError> {
+        Ok(serde_json::from_value(self.auth_request(Method::GET, "subscriptions").await?)?)
+    }
+
+    pub async fn subscribe(&self, author_id: &str) -> Result<(), ApiError> {
+        let endpoint = format!("subscriptions/{}", author_id);
+        self.auth_request(Method::POST, &endpoint).await.map(|_| ())
+    }
+
+    pub async fn unsubscribe(&self, author_id: &str) -> Result<(), ApiError> {
+        let endpoint = format!("subscriptions/{}", author_id);
+        self.auth_request(Method::DELETE, &endpoint).await.map(|_| ())
+    }
+
+    /// Whether `instance` plays streams through itself, by asking it for a
+    /// proxied stream of a known video. Instances that disable proxying
+    /// refuse, or redirect to YouTube instead.
+    pub async fn check_proxy(&self, instance: &str) -> Result<bool, ApiError> {
+        let url = format!("{}/latest_version", instance);
+        let params = [("id", PROXY_CHECK_VIDEO), ("itag", "18"), ("local", "true")];
+        let rsp = self.client.head(&url).query(&params).send().await?;
+        let status = rsp.status();
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            return Err(ApiError::RateLimited)
+        }
+        if status == StatusCode::FORBIDDEN || status == StatusCode::NOT_FOUND {
+            return Ok(false)
+        }
+        if !status.is_success() {
+            return Err(ApiError::Status(status.as_u16(), String::from("stream unavailable")))
+        }
+
+        let host = Url::parse(instance).ok().and_then(|url| url.host_str().map(String::from));
+        Ok(host.is_some() && rsp.url().host_str() == host.as_deref())
+    }
+}
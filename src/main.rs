@@ -8,6 +8,19 @@ mod handlers;
 mod ui;
 mod invidious;
 mod data;
+mod cli;
+mod fetch;
+mod input;
+mod store;
+mod history;
+mod commands;
+mod subscriptions;
+mod import;
+mod auth;
+mod playlists;
+mod watch_history;
+mod bookmarks;
+mod queue;
 
 use std::{
     io::{
@@ -52,27 +65,55 @@ use ui::{
 type Backend = TermionBackend<AlternateScreen<RawTerminal<Stdout>>>;
 
 fn main() -> Result<(), Error> {
-    setup_logger()?;
-    let config = config::Config::load_config()?;
+    let args = cli::Args::parse()?;
+    setup_logger(&args)?;
+    if let Some(path) = args.import {
+        return import_subscriptions(&path);
+    }
+    let mut config = config::Config::load_config()?;
+    if let Some(instance) = args.instance {
+        config.instance = Some(instance);
+    }
     let events = Events::default();
     let mut terminal = init_term()?;
     let (search_view_type, search_view) = init_search_view();
     let (home_view_type, home_view) = init_home_view();
     let (queue_view_type, queue_view) = init_queue_view();
-    let mut app = App::new(config)
+    let (subscriptions_view_type, subscriptions_view) = init_subscriptions_view();
+    let (playlists_view_type, playlists_view) = init_playlists_view();
+    let (history_view_type, history_view) = init_history_view();
+    let (bookmarks_view_type, bookmarks_view) = init_bookmarks_view();
+    let mut app = App::new(config, events.sender())
         .view(search_view_type, search_view)
         .view(home_view_type, home_view)
-        .view(queue_view_type, queue_view); 
+        .view(queue_view_type, queue_view)
+        .view(subscriptions_view_type, subscriptions_view)
+        .view(playlists_view_type, playlists_view)
+        .view(history_view_type, history_view)
+        .view(bookmarks_view_type, bookmarks_view);
     app.run_setup();
 
     while !app.quit {
-        if let Event::Input(key) = events.next()? {
-            handlers::event_handler(key, &mut app, &mut terminal)?;
+        match events.next()? {
+            Event::Input(key) => handlers::event_handler(key, &mut app, &mut terminal)?,
+            Event::Fetched(fetched) => handlers::fetch_handler(*fetched, &mut app),
+            Event::Tick => {
+                app.tick = app.tick.wrapping_add(1);
+                app.autoplay_related();
+                app.request_suggestions();
+                app.sync_queues();
+                app.track_watch_position();
+                app.save_queues(false);
+            },
         }
+        utils::reload_video_view(&mut app);
         ui::draw(&mut terminal, &mut app)?;
         utils::update_queue_view(&mut app);
     }
 
+    if let Err(e) = app.loaded_data.watch_history.save_changes() {
+        log::error!("Unable to save watch history: {}", e);
+    }
     Ok(())
 }
 
@@ -84,7 +125,28 @@ fn init_term() -> Result<Terminal<Backend>, Error> {
     Ok(terminal)
 }
 
-fn setup_logger() -> Result<(), fern::InitError> {
+/// Handles `--import`, which runs without starting the interface.
+fn import_subscriptions(path: &str) -> Result<(), Error> {
+    let channels = import::read_subscriptions(&import::expand_path(path))?;
+    let mut subscriptions = subscriptions::Subscriptions::load();
+    let (imported, skipped) = subscriptions.merge(channels);
+    subscriptions.save()?;
+    println!("Imported {} channels, skipped {} already subscribed", imported, skipped);
+    Ok(())
+}
+
+/// Logs to `--log-file`, or `output.log` in the state directory, at
+/// `--log-level` (debug by default).
+fn setup_logger(args: &cli::Args) -> Result<(), fern::InitError> {
+    let log_file = match &args.log_file {
+        Some(path) => import::expand_path(path),
+        None => store::state_dir()
+            .unwrap_or_else(std::env::temp_dir)
+            .join("output.log"),
+    };
+    if let Some(dir) = log_file.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
     fern::Dispatch::new()
         .format(|out, message, record| {
             out.finish(format_args!(
@@ -95,8 +157,8 @@ fn setup_logger() -> Result<(), fern::InitError> {
                 message
             ))
         })
-        .level(log::LevelFilter::Debug)
-        .chain(fern::log_file("/tmp/output.log")?)
+        .level(args.log_level.unwrap_or(log::LevelFilter::Debug))
+        .chain(fern::log_file(log_file)?)
         .apply()?;
     Ok(())
 }
@@ -132,3 +194,38 @@ fn init_queue_view() -> (ViewType, View) {
     ];
     (ViewType::Queue, View::new(queue_windows, queue_list, String::from("Queued Tracks")))
 }
+
+fn init_subscriptions_view() -> (ViewType, View) {
+    let subscriptions_list = vec!["Feed".to_owned(), "Channels".to_owned()];
+    let subscriptions_windows = vec![
+        Window::new("Feed".to_owned(), 0, ContentType::MediaContent(Arc::new(RwLock::new(vec![]))), Some(Box::new(table_info::VIDEO_HEADERS)), WindowType::SubscriptionFeed, Box::new(table_info::VIDEO_COLUMN_CONSTRAINTS)),
+        Window::new("Channels".to_owned(), 0, ContentType::MediaContent(Arc::new(RwLock::new(vec![]))), Some(Box::new(table_info::SUBSCRIPTION_HEADERS)), WindowType::SubscribedChannels, Box::new(table_info::QUEUE_COLUMN_CONSTRAINTS)),
+    ];
+    (ViewType::Subscriptions, View::new(subscriptions_windows, subscriptions_list, String::from("Subscriptions")))
+}
+
+fn init_playlists_view() -> (ViewType, View) {
+    let playlists_list = vec!["Playlists".to_owned()];
+    let playlists_windows = vec![
+        Window::new("Playlists".to_owned(), 0, ContentType::MediaContent(Arc::new(RwLock::new(vec![]))), Some(Box::new(table_info::LOCAL_PLAYLIST_HEADERS)), WindowType::LocalPlaylists, Box::new(table_info::DEFAULT_COLUMN_CONSTRAINTS)),
+    ];
+    (ViewType::Playlists, View::new(playlists_windows, playlists_list, String::from("Playlists")))
+}
+
+fn init_history_view() -> (ViewType, View) {
+    let history_list = vec!["Watched".to_owned()];
+    let history_windows = vec![
+        Window::new("Watched".to_owned(), 0, ContentType::MediaContent(Arc::new(RwLock::new(vec![]))), Some(Box::new(table_info::HISTORY_HEADERS)), WindowType::WatchHistory, Box::new(table_info::HISTORY_COLUMN_CONSTRAINTS)),
+    ];
+    (ViewType::History, View::new(history_windows, history_list, String::from("History")))
+}
+
+fn init_bookmarks_view() -> (ViewType, View) {
+    let bookmarks_list = vec!["Videos".to_owned(), "Playlists".to_owned(), "Channels".to_owned()];
+    let bookmarks_windows = vec![
+        Window::new("Videos".to_owned(), 0, ContentType::MediaContent(Arc::new(RwLock::new(vec![]))), Some(Box::new(table_info::BOOKMARK_HEADERS)), WindowType::BookmarkedVideos, Box::new(table_info::BOOKMARK_COLUMN_CONSTRAINTS)),
+        Window::new("Playlists".to_owned(), 0, ContentType::MediaContent(Arc::new(RwLock::new(vec![]))), Some(Box::new(table_info::BOOKMARK_HEADERS)), WindowType::BookmarkedPlaylists, Box::new(table_info::BOOKMARK_COLUMN_CONSTRAINTS)),
+        Window::new("Channels".to_owned(), 0, ContentType::MediaContent(Arc::new(RwLock::new(vec![]))), Some(Box::new(table_info::BOOKMARK_CHANNEL_HEADERS)), WindowType::BookmarkedChannels, Box::new(table_info::QUEUE_COLUMN_CONSTRAINTS)),
+    ];
+    (ViewType::Bookmarks, View::new(bookmarks_windows, bookmarks_list, String::from("Bookmarks")))
+}
@@ -1,8 +1,8 @@
-use serde::Deserialize;
-use tokio::runtime::Runtime;
-use failure::Error;
+use serde::{
+    Serialize,
+    Deserialize,
+};
 use serde_json::Value;
-use reqwest::Client;
 use crate::{
     ui::{
         views::{
@@ -13,23 +13,28 @@ use crate::{
         },
         table_info,
     },
-    data::LoadedData,
     media::{
         Media,
         ListItem,
         playlist::Playlist,
         video::Video,
     },
-    utils,
-    invidious,
+    invidious::{
+        Invidious,
+        ApiError,
+    },
+    fetch::{
+        Fetcher,
+        Fetched,
+    },
 };
 use std::sync::{
     Arc,
     RwLock,
 };
 
-#[derive(Clone, Debug, Deserialize, PartialEq)]
-#[serde(rename_all(deserialize = "camelCase"), default)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
 pub struct Channel {
     pub author: String,
     pub author_id: String,
@@ -38,16 +43,9 @@ pub struct Channel {
     pub sub_count: u64,
 }
 
-const CHANNEL_VIDEOS_URL: &str = "https://www.invidio.us/api/v1/channels/videos";
-const CHANNEL_PLAYLISTS_URL: &str = "https://www.invidio.us/api/v1/channels/playlists";
 impl Channel {
-    async fn get_playlists(&self, params: Vec<(&str, &str)>, client: &Client, url: &str) -> Result<Vec<Playlist>, reqwest::Error> {
-        let rsp = client.get(url)
-            .query(&params)
-            .send()
-            .await?;
-        
-        let json: Value = rsp.json().await?;
+    async fn get_playlists(&self, params: Vec<(&str, &str)>, api: &Invidious, endpoint: &str) -> Result<Vec<Playlist>, ApiError> {
+        let json: Value = api.get_json(endpoint, &params).await?;
         if json["playlists"].is_array() {
             match serde_json::from_value::<Vec<Playlist>>(json["playlists"].clone()) {
                 Ok(playlists) => return Ok(playlists),
@@ -60,65 +58,69 @@ impl Channel {
         Ok(vec![])
     }
     
-    pub async fn get_channel_media(&self, client: &Client) -> Result<(Vec<Video>, Vec<Playlist>), reqwest::Error> {
+    pub async fn get_channel_media(&self, api: &Invidious) -> Result<(Vec<Video>, Vec<Playlist>), ApiError> {
         let id = &self.author_id;
-        let video_url = format!("{}/{}", CHANNEL_VIDEOS_URL, id);
-        let playlist_url = format!("{}/{}", CHANNEL_PLAYLISTS_URL, id);
-        log::debug!("{:#}", video_url);
+        let video_endpoint = format!("channels/videos/{}", id);
+        let playlist_endpoint = format!("channels/playlists/{}", id);
+        log::debug!("{:#}", api.api_url(&video_endpoint));
         let params = vec![
             ("page", "1"),
             ("sort_by", "newest"),
         ];
-        let videos_fut = invidious::invidious_videos(params, client, &video_url);
+        let videos_fut = api.videos(params, &video_endpoint);
 
         let params = vec![
             ("sort_by", "newest"),
         ];
-        //let playlists_fut = invidious::invidious_playlists(params, client, &playlist_url);
-        let playlists_fut = self.get_playlists(params, client, &playlist_url);
+        //let playlists_fut = api.playlists(params, &playlist_endpoint);
+        let playlists_fut = self.get_playlists(params, api, &playlist_endpoint);
         futures::try_join!(videos_fut, playlists_fut)
     }
 }
 
 impl Media for Channel {
-    fn open(&self, client: &Client, runtime: Arc<RwLock<Runtime>>, loaded_data: &mut LoadedData) -> Result<View, Error> {
-        let (videos, playlists) = runtime.write().unwrap().block_on(self.get_channel_media(client))?;
+    fn open(&self, api: &Invidious, fetcher: &Fetcher) -> Option<View> {
+        let channel = self.clone();
+        let api = api.clone();
+        fetcher.spawn(async move {
+            let result = channel.get_channel_media(&api).await;
+            Fetched::Channel(channel.author_id, result)
+        });
 
         let video_title = format!("{}'s Videos", self.author());
-        let video_text = utils::video_to_text(videos.clone());
-
         let playlist_title = format!("{}'s Playlists", self.author());
-        let playlist_text = utils::playlist_to_text(playlists.clone());
-
-        loaded_data.channel_videos = videos;
-        loaded_data.channel_playlists = playlists;
 
         let tabs = vec!["Videos".to_owned(), "Playlists".to_owned()];
         let root_windows = vec![
             Window::new(
                 video_title,
                 0,
-                ContentType::MediaContent(Arc::new(RwLock::new(video_text))),
+                ContentType::MediaContent(Arc::new(RwLock::new(vec![]))),
                 Some(Box::new(table_info::VIDEO_HEADERS)),
                 WindowType::ChannelVideos,
                 Box::new(table_info::VIDEO_COLUMN_CONSTRAINTS),
-                ),
+                ).loading(),
                 Window::new(
                     playlist_title,
                     0,
-                    ContentType::MediaContent(Arc::new(RwLock::new(playlist_text))),
+                    ContentType::MediaContent(Arc::new(RwLock::new(vec![]))),
                     Some(Box::new(table_info::PLAYLIST_HEADERS)),
                     WindowType::ChannelPlaylists,
                     Box::new(table_info::DEFAULT_COLUMN_CONSTRAINTS),
-                    ),
+                    ).loading(),
         ];
-        let view = View::new(root_windows, tabs, self.title());
-        Ok(view)
+        let view = View::new(root_windows, tabs, self.title())
+            .for_media(self.author_id.clone());
+        Some(view)
     }
     
     fn author(&self) -> String {
         self.author.clone()
     }
+
+    fn author_id(&self) -> String {
+        self.author_id.clone()
+    }
 }
 
 impl ListItem for Channel {
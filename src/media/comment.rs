@@ -0,0 +1,241 @@
+use serde::Deserialize;
+use crate::media::ListItem;
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all(deserialize = "camelCase"), default)]
+pub struct Comment {
+    pub author: String,
+    pub author_id: String,
+    pub content: String,
+    pub published_text: String,
+    pub like_count: i64,
+    pub comment_id: String,
+    pub replies: Option<Replies>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all(deserialize = "camelCase"), default)]
+pub struct Replies {
+    pub reply_count: u64,
+    pub continuation: String,
+}
+
+/// One page of `/api/v1/comments/:id`, either top level comments or replies.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all(deserialize = "camelCase"), default)]
+pub struct CommentPage {
+    pub comments: Vec<Comment>,
+    pub continuation: Option<String>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum CommentSort {
+    Top,
+    New,
+}
+
+impl CommentSort {
+    pub fn as_param(self) -> &'static str {
+        match self {
+            CommentSort::Top => "top",
+            CommentSort::New => "new",
+        }
+    }
+
+    pub fn toggle(self) -> Self {
+        match self {
+            CommentSort::Top => CommentSort::New,
+            CommentSort::New => CommentSort::Top,
+        }
+    }
+}
+
+/// What a fetched comment page should be applied to. `NextPage` and
+/// `Replies` carry the continuation token they were requested with.
+#[derive(Clone, Debug, PartialEq)]
+pub enum CommentTarget {
+    Reload,
+    NextPage(String),
+    Replies(String),
+}
+
+impl CommentTarget {
+    pub fn continuation(&self) -> Option<&str> {
+        match self {
+            CommentTarget::Reload => None,
+            CommentTarget::NextPage(continuation) => Some(continuation),
+            CommentTarget::Replies(continuation) => Some(continuation),
+        }
+    }
+}
+
+/// A row of the comments window. Replies are inserted after their parent
+/// with a greater depth; `more_replies` rows load the next page of replies.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CommentEntry {
+    pub comment: Comment,
+    pub depth: usize,
+    pub expanded: bool,
+    pub more_replies: Option<String>,
+}
+
+impl CommentEntry {
+    fn new(comment: Comment, depth: usize) -> Self {
+        CommentEntry {
+            comment,
+            depth,
+            expanded: false,
+            more_replies: None,
+        }
+    }
+
+    fn more(continuation: String, depth: usize) -> Self {
+        CommentEntry {
+            comment: Comment::default(),
+            depth,
+            expanded: false,
+            more_replies: Some(continuation),
+        }
+    }
+
+    /// Continuation that loads this entry's replies, if it has any.
+    pub fn reply_continuation(&self) -> Option<&str> {
+        match &self.more_replies {
+            Some(continuation) => Some(continuation),
+            None => self.comment.replies
+                .as_ref()
+                .map(|replies| replies.continuation.as_str())
+                .filter(|continuation| !continuation.is_empty()),
+        }
+    }
+}
+
+impl ListItem for CommentEntry {
+    fn into_text(&self) -> Vec<String> {
+        let indent = "  ".repeat(self.depth);
+        if self.more_replies.is_some() {
+            return vec![format!("{}↳ Load more replies…", indent), String::new(), String::new(), String::new()]
+        }
+
+        let marker = match &self.comment.replies {
+            Some(replies) if self.expanded => format!("[-{}] ", replies.reply_count),
+            Some(replies) => format!("[+{}] ", replies.reply_count),
+            None => String::new(),
+        };
+        let author = format!("{}{}{}", indent, marker, self.comment.author);
+        let content = self.comment.content
+            .split_whitespace()
+            .collect::<Vec<&str>>()
+            .join(" ");
+        let likes = format!("{} likes", self.comment.like_count);
+        vec![author, content, likes, self.comment.published_text.clone()]
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Comments {
+    pub video_id: String,
+    pub sort: CommentSort,
+    pub continuation: Option<String>,
+    pub entries: Vec<CommentEntry>,
+}
+
+impl Comments {
+    pub fn new(video_id: String, sort: CommentSort) -> Self {
+        Comments {
+            video_id,
+            sort,
+            continuation: None,
+            entries: vec![],
+        }
+    }
+
+    pub fn append_page(&mut self, page: CommentPage) {
+        self.continuation = page.continuation;
+        self.entries.extend(page.comments.into_iter().map(|comment| CommentEntry::new(comment, 0)));
+    }
+
+    /// Inserts a page of replies in place of the row that requested them:
+    /// after an expanded parent, or replacing a "load more" row.
+    pub fn insert_replies(&mut self, continuation: &str, page: CommentPage) {
+        let index = match self.entries.iter().position(|entry| entry.reply_continuation() == Some(continuation)) {
+            Some(index) => index,
+            None => return,
+        };
+
+        let (insert_at, depth) = if self.entries[index].more_replies.is_some() {
+            let entry = self.entries.remove(index);
+            (index, entry.depth)
+        }
+        else {
+            self.entries[index].expanded = true;
+            (index + 1, self.entries[index].depth + 1)
+        };
+
+        let mut replies: Vec<CommentEntry> = page.comments
+            .into_iter()
+            .map(|comment| CommentEntry::new(comment, depth))
+            .collect();
+        if let Some(continuation) = page.continuation {
+            replies.push(CommentEntry::more(continuation, depth));
+        }
+        self.entries.splice(insert_at..insert_at, replies);
+    }
+
+    /// Removes every reply nested under the entry at `index`.
+    pub fn collapse(&mut self, index: usize) {
+        let depth = match self.entries.get_mut(index) {
+            Some(entry) => {
+                entry.expanded = false;
+                entry.depth
+            },
+            None => return,
+        };
+
+        let end = self.entries[index + 1..]
+            .iter()
+            .position(|entry| entry.depth <= depth)
+            .map(|offset| index + 1 + offset)
+            .unwrap_or_else(|| self.entries.len());
+        self.entries.drain(index + 1..end);
+    }
+
+    pub fn to_text(&self) -> Vec<Vec<String>> {
+        self.entries
+            .iter()
+            .map(|entry| entry.into_text())
+            .collect()
+    }
+}
+
+impl Default for Comment {
+    fn default() -> Self {
+        Comment {
+            author: String::new(),
+            author_id: String::new(),
+            content: String::new(),
+            published_text: String::new(),
+            like_count: 0,
+            comment_id: String::new(),
+            replies: None,
+        }
+    }
+}
+
+impl Default for Replies {
+    fn default() -> Self {
+        Replies {
+            reply_count: 0,
+            continuation: String::new(),
+        }
+    }
+}
+
+impl Default for CommentPage {
+    fn default() -> Self {
+        CommentPage {
+            comments: vec![],
+            continuation: None,
+        }
+    }
+}
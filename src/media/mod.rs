@@ -1,28 +1,24 @@
 use crate::{
     player::Player,
     ui::views::View,
-    data::LoadedData,
-};
-use failure::Error;
-use reqwest::Client;
-use tokio::runtime::Runtime;
-use std::sync::{
-    Arc,
-    RwLock,
+    invidious::Invidious,
+    fetch::Fetcher,
 };
 
 pub mod video;
 pub mod playlist;
 pub mod channel;
+pub mod comment;
 
 pub trait Media {
-    fn open(&self, _: &Client, _: Arc<RwLock<Runtime>>, _: &mut LoadedData) -> Result<View, Error> {
-        Err(failure::format_err!("")) 
+    /// Returns a view that is filled in once `Fetched` data for it arrives.
+    fn open(&self, _: &Invidious, _: &Fetcher) -> Option<View> {
+        None
     }
 
-    fn play_video(&self, _: &mut Player) {}
+    fn play_video(&self, _: &mut Player, _: &Invidious) {}
 
-    fn play_audio(&self, _: &mut Player) {}
+    fn play_audio(&self, _: &mut Player, _: &Invidious) {}
 
     fn title(&self) -> String {
         String::new()
@@ -32,7 +28,11 @@ pub trait Media {
         String::new()
     }
 
-    fn url(&self) -> String {
+    fn author_id(&self) -> String {
+        String::new()
+    }
+
+    fn url(&self, _: &Invidious) -> String {
         String::new()
     }
 }
@@ -1,10 +1,13 @@
-use serde::Deserialize;
-use tokio::runtime::Runtime;
-use failure::Error;
-use serde_json::Value;
-use reqwest::Client;
+use serde::{
+    Serialize,
+    Deserialize,
+};
 use crate::{
     player::Player,
+    invidious::{
+        Invidious,
+        ApiError,
+    },
     ui::{
         views::{
             Window,
@@ -14,21 +17,23 @@ use crate::{
         },
         table_info,
     },
-    data::LoadedData,
     media::{
         Media,
         ListItem,
         video::Video,
     },
-    utils,
+    fetch::{
+        Fetcher,
+        Fetched,
+    },
 };
 use std::sync::{
     Arc,
     RwLock,
 };
 
-#[derive(Clone, Debug, Deserialize, PartialEq)]
-#[serde(rename_all(deserialize = "camelCase"), default)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
 pub struct Playlist {
     pub title: String,
     pub playlist_id: String,
@@ -38,24 +43,14 @@ pub struct Playlist {
     pub video_count: u64,
 }
 
-const PLAYLIST_URL: &str = "https://www.invidio.us/api/v1/playlists";
 impl Playlist {
-    pub async fn get_videos(&self, client: &Client, total_pages: u32) -> Result<Vec<Video>, Error> {
-        let id = &self.playlist_id;
-        let url = format!("{}/{}", PLAYLIST_URL, id);
+    pub async fn get_videos(&self, api: &Invidious, total_pages: u32) -> Result<Vec<Video>, ApiError> {
+        let endpoint = format!("playlists/{}", &self.playlist_id);
         let mut playlist_videos = vec![];
-        log::debug!("PLAYLIST_VIDEO_URL: {:?}", url);
+        log::debug!("PLAYLIST_VIDEO_URL: {:?}", api.api_url(&endpoint));
         
         for page in 0..total_pages {
-            let params = [
-                ("page", page + 1),
-            ];
-
-            let rsp = client.get(&url)
-                .query(&params)
-                .send()
-                .await?;
-            let json: Value = rsp.json().await?;
+            let json = api.playlist_page(&self.playlist_id, page + 1).await?;
             if json["videos"].is_array() {
                 match serde_json::from_value::<Vec<Video>>(json["videos"].clone()) {
                     Ok(mut videos) => {
@@ -63,7 +58,7 @@ impl Playlist {
                     },
                     Err(e) => {
                         log::error!("Unable to convert to type Video: {}", e);
-                        return Err(failure::format_err!("{}", e))
+                        return Err(e.into())
                     }
                 }
             }
@@ -76,37 +71,39 @@ impl Playlist {
 }
 
 impl Media for Playlist {
-    fn open(&self, client: &Client, runtime: Arc<RwLock<Runtime>>, loaded_data: &mut LoadedData) -> Result<View, Error> {
-        let videos = runtime.write().unwrap().block_on(self.get_videos(client, 1))?;
-        let video_text = utils::video_to_text(videos.clone());
-        
-        loaded_data.playlist_videos = videos;
+    fn open(&self, api: &Invidious, fetcher: &Fetcher) -> Option<View> {
+        let playlist = self.clone();
+        let api = api.clone();
+        fetcher.spawn(async move {
+            let result = playlist.get_videos(&api, 1).await;
+            Fetched::Playlist(playlist.playlist_id, result)
+        });
+
         let window = Window::new(
             self.title.clone(),
             0,
-            ContentType::MediaContent(Arc::new(RwLock::new(video_text))),
+            ContentType::MediaContent(Arc::new(RwLock::new(vec![]))),
             Some(Box::new(table_info::PLAYLIST_VIDEO_HEADERS)),
             WindowType::PlaylistVideos,
             Box::new(table_info::DEFAULT_COLUMN_CONSTRAINTS),
-            );
-        let view = View::new(vec![window], vec!["Videos".to_owned()], self.title());
-        Ok(view)
+            ).loading();
+        let view = View::new(vec![window], vec!["Videos".to_owned()], self.title())
+            .for_media(self.playlist_id.clone());
+        Some(view)
     }
     
-    fn play_video(&self, player: &mut Player) {
-        let id = &self.playlist_id;
-        let url = format!("https://invidio.us/playlist?list={}", id);
+    fn play_video(&self, player: &mut Player, api: &Invidious) {
+        let url = api.playlist_url(&self.playlist_id);
         player.play(url, true);
     }
     
-    fn play_audio(&self, player: &mut Player) {
-        let id = &self.playlist_id;
-        let url = format!("https://invidio.us/playlist?list={}", id);
+    fn play_audio(&self, player: &mut Player, api: &Invidious) {
+        let url = api.playlist_url(&self.playlist_id);
         player.play(url, false);
     }
     
-    fn url(&self) -> String {
-        format!("https://invidio.us/playlist?list={}", &self.playlist_id)
+    fn url(&self, api: &Invidious) -> String {
+        api.playlist_url(&self.playlist_id)
     }
     
     fn title(&self) -> String {
@@ -116,6 +113,10 @@ impl Media for Playlist {
     fn author(&self) -> String {
         self.author.clone()
     }
+
+    fn author_id(&self) -> String {
+        self.author_id.clone()
+    }
 }
 
 
@@ -1,14 +1,46 @@
-use serde::Deserialize;
+use serde::{
+    Serialize,
+    Deserialize,
+    Deserializer,
+};
+use serde_json::Value;
+use chrono::{
+    Local,
+    TimeZone,
+};
 use crate::{
     player::Player,
+    invidious::Invidious,
     media::{
         Media,
         ListItem,
+        comment::{
+            CommentSort,
+            CommentTarget,
+        },
+    },
+    ui::{
+        views::{
+            Window,
+            WindowType,
+            ContentType,
+            View,
+        },
+        table_info,
     },
+    fetch::{
+        Fetcher,
+        Fetched,
+    },
+    utils,
+};
+use std::sync::{
+    Arc,
+    RwLock,
 };
 
-#[derive(Debug, Clone, Deserialize, PartialEq)]
-#[serde(rename_all(deserialize = "camelCase"), default)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
 pub struct Video {
     pub title: String,
     pub video_id: String,
@@ -18,23 +50,22 @@ pub struct Video {
     pub author_id: String,
     pub author_url: String,
     pub published_text: String,
+    pub published: i64,
 }
 
 impl Media for Video {
-    fn play_video(&self, player: &mut Player) {
-        let id = &self.video_id;
-        let url = format!("https://invidio.us/watch?v={}", id);
+    fn play_video(&self, player: &mut Player, api: &Invidious) {
+        let url = api.watch_url(&self.video_id);
         player.play(url, true);
     }
     
-    fn play_audio(&self, player: &mut Player) {
-        let id = &self.video_id;
-        let url = format!("https://invidio.us/watch?v={}", id);
+    fn play_audio(&self, player: &mut Player, api: &Invidious) {
+        let url = api.watch_url(&self.video_id);
         player.play(url, false);
     }
 
-    fn url(&self) -> String {
-        format!("https://invidio.us/watch?v={}", &self.video_id)
+    fn url(&self, api: &Invidious) -> String {
+        api.watch_url(&self.video_id)
     }
 
     fn title(&self) -> String {
@@ -45,11 +76,256 @@ impl Media for Video {
         self.author.clone()
     }
 
-    /*
-    fn open(&self, client: &Client, _: Arc<RwLock<Runtime>>, data: &mut LoadedData)  -> Result<View, Error> {
+    fn author_id(&self) -> String {
+        self.author_id.clone()
+    }
+
+    fn open(&self, api: &Invidious, fetcher: &Fetcher) -> Option<View> {
+        let video_id = self.video_id.clone();
+        let video_api = api.clone();
+        fetcher.spawn(async move {
+            let result = video_api.video(&video_id).await;
+            Fetched::Video(video_id, result)
+        });
+        utils::fetch_comments(api, fetcher, self.video_id.clone(), CommentSort::Top, CommentTarget::Reload);
+
+        let tabs = vec!["Details".to_owned(), "Related".to_owned(), "Formats".to_owned(), "Comments".to_owned()];
+        let root_windows = vec![
+            Window::new(
+                self.title.clone(),
+                0,
+                ContentType::TextContent(vec![]),
+                None,
+                WindowType::VideoDetails,
+                Box::new([]),
+                ).loading(),
+            Window::new(
+                format!("Related to {}", self.title),
+                0,
+                ContentType::MediaContent(Arc::new(RwLock::new(vec![]))),
+                Some(Box::new(table_info::VIDEO_HEADERS)),
+                WindowType::VideoRelated,
+                Box::new(table_info::VIDEO_COLUMN_CONSTRAINTS),
+                ).loading(),
+            Window::new(
+                format!("Formats of {}", self.title),
+                0,
+                ContentType::MediaContent(Arc::new(RwLock::new(vec![]))),
+                Some(Box::new(table_info::FORMAT_HEADERS)),
+                WindowType::VideoFormats,
+                Box::new(table_info::FORMAT_COLUMN_CONSTRAINTS),
+                ).loading(),
+            Window::new(
+                format!("Comments on {} (top)", self.title),
+                0,
+                ContentType::MediaContent(Arc::new(RwLock::new(vec![]))),
+                Some(Box::new(table_info::COMMENT_HEADERS)),
+                WindowType::VideoComments,
+                Box::new(table_info::COMMENT_COLUMN_CONSTRAINTS),
+                ).loading(),
+        ];
+        let view = View::new(root_windows, tabs, self.title())
+            .for_media(self.video_id.clone());
+        Some(view)
+    }
+}
+
+/// A stream listed in the `formatStreams`/`adaptiveFormats` of a video.
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(rename_all(deserialize = "camelCase"), default)]
+pub struct Format {
+    pub url: String,
+    #[serde(deserialize_with = "lenient_string")]
+    pub itag: String,
+    #[serde(rename(deserialize = "type"))]
+    pub mime_type: String,
+    pub container: String,
+    pub encoding: String,
+    pub quality: String,
+    pub quality_label: String,
+    pub resolution: String,
+    pub audio_quality: String,
+    #[serde(deserialize_with = "lenient_string")]
+    pub bitrate: String,
+    /// Whether the format is from `adaptiveFormats`, which have either video
+    /// or audio but not both.
+    #[serde(skip)]
+    pub adaptive: bool,
+}
+
+impl Format {
+    pub fn quality(&self) -> String {
+        [&self.quality_label, &self.resolution, &self.quality, &self.audio_quality]
+            .iter()
+            .find(|quality| !quality.is_empty())
+            .map(|quality| quality.to_string())
+            .unwrap_or_default()
+    }
+
+    pub fn is_audio(&self) -> bool {
+        self.mime_type.starts_with("audio/")
+    }
+
+    pub fn is_video_only(&self) -> bool {
+        self.adaptive && !self.is_audio()
+    }
+
+    /// Bitrate in bit/s, 0 when unknown.
+    fn bitrate_value(&self) -> u64 {
+        self.bitrate.parse().unwrap_or(0)
+    }
+}
+
+/// Audio stream to play along a video-only format: the best one up to
+/// `max_bitrate` kbit/s (0 for no limit), or the lowest when all are above.
+pub fn best_audio(formats: &[Format], max_bitrate: u32) -> Option<&Format> {
+    let mut audio: Vec<&Format> = formats
+        .iter()
+        .filter(|format| format.adaptive && format.is_audio())
+        .collect();
+    audio.sort_by_key(|format| format.bitrate_value());
+    let limit = u64::from(max_bitrate) * 1000;
+    audio.iter()
+        .rev()
+        .find(|format| limit == 0 || format.bitrate_value() <= limit)
+        .or_else(|| audio.first())
+        .cloned()
+}
+
+impl ListItem for Format {
+    fn into_text(&self) -> Vec<String> {
+        let mime_type = self.mime_type
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .to_owned();
+        let quality = self.quality();
+        let codec = if self.encoding.is_empty() {
+            self.container.clone()
+        }
+        else {
+            format!("{} ({})", self.container, self.encoding)
+        };
+        vec![self.itag.clone(), mime_type, quality, codec]
+    }
+}
+
+impl Default for Format {
+    fn default() -> Self {
+        Format {
+            url: String::new(),
+            itag: String::new(),
+            mime_type: String::new(),
+            container: String::new(),
+            encoding: String::new(),
+            quality: String::new(),
+            quality_label: String::new(),
+            resolution: String::new(),
+            audio_quality: String::new(),
+            bitrate: String::new(),
+            adaptive: false,
+        }
+    }
+}
+
+/// Full response of `/api/v1/videos/:id`.
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(rename_all(deserialize = "camelCase"), default)]
+pub struct VideoDetails {
+    pub title: String,
+    pub video_id: String,
+    pub description: String,
+    pub published: i64,
+    pub published_text: String,
+    pub keywords: Vec<String>,
+    pub view_count: u64,
+    pub like_count: u64,
+    pub length_seconds: i64,
+    pub author: String,
+    pub author_id: String,
+    pub format_streams: Vec<Format>,
+    pub adaptive_formats: Vec<Format>,
+    pub recommended_videos: Vec<Video>,
+}
+
+impl VideoDetails {
+    pub fn to_lines(&self) -> Vec<String> {
+        let published = Local.timestamp_opt(self.published, 0)
+            .single()
+            .map(|date| format!("{} ({})", date.format("%Y-%m-%d"), self.published_text))
+            .unwrap_or_else(|| self.published_text.clone());
+
+        let mut lines = vec![
+            format!("Title: {}", self.title),
+            format!("Channel: {}", self.author),
+            format!("Published: {}", published),
+            format!("Length: {}", utils::format_seconds(self.length_seconds)),
+            format!("Views: {}", utils::format_count(self.view_count)),
+            format!("Likes: {}", utils::format_count(self.like_count)),
+            format!("Keywords: {}", self.keywords.join(", ")),
+            String::new(),
+        ];
+        lines.extend(self.description.lines().map(String::from));
+        lines
+    }
+
+    pub fn formats(&self) -> Vec<Format> {
+        let adaptive_formats = self.adaptive_formats
+            .iter()
+            .cloned()
+            .map(|format| Format { adaptive: true, ..format });
+        self.format_streams
+            .iter()
+            .cloned()
+            .chain(adaptive_formats)
+            .collect()
+    }
+
+    pub fn to_video(&self) -> Video {
+        Video {
+            title: self.title.clone(),
+            video_id: self.video_id.clone(),
+            length_seconds: self.length_seconds,
+            author: self.author.clone(),
+            author_id: self.author_id.clone(),
+            published_text: self.published_text.clone(),
+            published: self.published,
+            ..Video::default()
+        }
+    }
+}
+
+impl Default for VideoDetails {
+    fn default() -> Self {
+        VideoDetails {
+            title: String::new(),
+            video_id: String::new(),
+            description: String::new(),
+            published: 0,
+            published_text: String::new(),
+            keywords: vec![],
+            view_count: 0,
+            like_count: 0,
+            length_seconds: 0,
+            author: String::new(),
+            author_id: String::new(),
+            format_streams: vec![],
+            adaptive_formats: vec![],
+            recommended_videos: vec![],
+        }
+    }
+}
 
+/// Instances disagree on whether some numeric fields are strings or numbers.
+fn lenient_string<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Value::deserialize(deserializer)? {
+        Value::String(s) => Ok(s),
+        Value::Null => Ok(String::new()),
+        value => Ok(value.to_string()),
     }
-    */
 }
 
 impl ListItem for Video {
@@ -87,6 +363,7 @@ impl Default for Video {
             author_id: String::new(),
             author_url: String::new(),
             published_text: String::new(),
+            published: 0,
         }
     }
 }
@@ -0,0 +1,506 @@
+use std::os::unix::net::UnixStream;
+use std::net::Shutdown;
+use std::io::{
+    self,
+    Read,
+    Write,
+};
+use std::{
+    fmt,
+    thread,
+    path::Path,
+    collections::VecDeque,
+    time::{
+        Duration,
+        Instant,
+    },
+};
+use serde::{
+    Deserialize,
+    de::DeserializeOwned,
+};
+use serde_json::{
+    Value,
+    json,
+};
+
+/// How long to wait for mpv to answer a request.
+const REPLY_TIMEOUT: Duration = Duration::from_secs(2);
+/// How long mpv may take to accept a whole request.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(2);
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+#[derive(Debug)]
+pub enum IpcError {
+    Io(io::Error),
+    Decode(serde_json::Error),
+    /// mpv didn't open its socket, or didn't answer a request, in time.
+    Timeout,
+    /// mpv exited or closed the socket.
+    Closed,
+    /// mpv answered a request with an error other than "success".
+    Mpv(String),
+}
+
+impl fmt::Display for IpcError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IpcError::Io(e) => write!(f, "IO error: {}", e),
+            IpcError::Decode(e) => write!(f, "Unable to decode mpv reply: {}", e),
+            IpcError::Timeout => write!(f, "Timed out waiting for mpv"),
+            IpcError::Closed => write!(f, "mpv closed the connection"),
+            IpcError::Mpv(e) => write!(f, "mpv error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for IpcError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            IpcError::Io(e) => Some(e),
+            IpcError::Decode(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for IpcError {
+    fn from(e: io::Error) -> Self {
+        IpcError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for IpcError {
+    fn from(e: serde_json::Error) -> Self {
+        IpcError::Decode(e)
+    }
+}
+
+/// Reply to a request, matched to it by `request_id`.
+#[derive(Deserialize, Debug)]
+struct MpvResponse<T> {
+    data: Option<T>,
+    #[serde(default)]
+    request_id: u64,
+    error: String,
+}
+
+/// Message mpv sends on its own, like `property-change` for observed
+/// properties.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct MpvEvent {
+    pub event: String,
+    /// Observer id, for `property-change`.
+    #[serde(default)]
+    pub id: Option<u64>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub data: Value,
+    /// Why a file ended, for `end-file`.
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+/// Client for mpv's JSON IPC protocol: one JSON message per line, requests
+/// tagged with a `request_id` and answered with the same one, and events
+/// interleaved with the replies.
+pub struct IpcClient {
+    stream: UnixStream,
+    /// Bytes read that don't end in a newline yet.
+    buffer: Vec<u8>,
+    events: VecDeque<MpvEvent>,
+    next_request_id: u64,
+    /// Set once mpv has gone away: the socket was closed or a write failed.
+    closed: bool,
+}
+
+impl IpcClient {
+    /// Connects to the socket at `path`, retrying until mpv creates it or
+    /// `timeout` runs out.
+    pub fn connect<P: AsRef<Path>>(path: P, timeout: Duration) -> Result<Self, IpcError> {
+        let start = Instant::now();
+        loop {
+            match UnixStream::connect(path.as_ref()) {
+                Ok(stream) => return IpcClient::from_stream(stream),
+                Err(e) => {
+                    if start.elapsed() >= timeout {
+                        log::debug!("Unable to connect to {}: {}", path.as_ref().display(), e);
+                        return Err(IpcError::Timeout)
+                    }
+                    thread::sleep(POLL_INTERVAL);
+                },
+            }
+        }
+    }
+
+    fn from_stream(stream: UnixStream) -> Result<Self, IpcError> {
+        stream.set_nonblocking(true)?;
+        Ok(IpcClient {
+            stream,
+            buffer: vec![],
+            events: VecDeque::new(),
+            next_request_id: 1,
+            closed: false,
+        })
+    }
+
+    /// Sends a command without waiting for the reply, which is skipped when
+    /// it arrives. Returns the request id.
+    pub fn command(&mut self, args: &[Value]) -> Result<u64, IpcError> {
+        let request_id = self.next_request_id;
+        self.next_request_id += 1;
+        self.send(&json!({ "command": args, "request_id": request_id }))?;
+        Ok(request_id)
+    }
+
+    /// Sends a command and waits for its reply, keeping the events that
+    /// arrive in the meantime for `poll_events`.
+    pub fn request<T: DeserializeOwned>(&mut self, args: &[Value]) -> Result<Option<T>, IpcError> {
+        let request_id = self.command(args)?;
+        let start = Instant::now();
+        loop {
+            self.fill_buffer()?;
+            while let Some(message) = self.next_message() {
+                if message.get("event").is_some() {
+                    self.push_event(message);
+                    continue
+                }
+                let response: MpvResponse<Value> = serde_json::from_value(message)?;
+                if response.request_id != request_id {
+                    continue
+                }
+                if response.error != "success" {
+                    return Err(IpcError::Mpv(response.error))
+                }
+                return match response.data {
+                    Some(data) => Ok(Some(serde_json::from_value(data)?)),
+                    None => Ok(None),
+                }
+            }
+            if self.closed {
+                return Err(IpcError::Closed)
+            }
+            if start.elapsed() >= REPLY_TIMEOUT {
+                return Err(IpcError::Timeout)
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    pub fn get_property<T: DeserializeOwned>(&mut self, name: &str) -> Result<T, IpcError> {
+        self.request::<T>(&[json!("get_property"), json!(name)])?
+            .ok_or_else(|| IpcError::Mpv(format!("property {} unavailable", name)))
+    }
+
+    pub fn set_property(&mut self, name: &str, value: Value) -> Result<(), IpcError> {
+        self.command(&[json!("set_property"), json!(name), value]).map(|_| ())
+    }
+
+    /// Asks mpv to send a `property-change` event with `id` whenever `name`
+    /// changes.
+    pub fn observe_property(&mut self, id: u64, name: &str) -> Result<(), IpcError> {
+        self.command(&[json!("observe_property"), json!(id), json!(name)]).map(|_| ())
+    }
+
+    /// Events received since the last call, without blocking. Fails with
+    /// `Closed` once mpv has exited and every event has been handed out.
+    pub fn poll_events(&mut self) -> Result<Vec<MpvEvent>, IpcError> {
+        if !self.closed {
+            self.fill_buffer()?;
+        }
+        while let Some(message) = self.next_message() {
+            if message.get("event").is_some() {
+                self.push_event(message);
+            }
+            else if message["error"] != "success" {
+                log::error!("mpv request failed: {}", message);
+            }
+        }
+        if self.closed && self.events.is_empty() {
+            return Err(IpcError::Closed)
+        }
+        Ok(self.events.drain(..).collect())
+    }
+
+    fn send(&mut self, message: &Value) -> Result<(), IpcError> {
+        if self.closed {
+            return Err(IpcError::Closed)
+        }
+        let line = format!("{}\n", message);
+        // Written in blocking mode, as a full socket buffer would otherwise
+        // cut the line off wherever the write gave up.
+        self.stream.set_nonblocking(false)?;
+        self.stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+        let res = self.stream.write_all(line.as_bytes());
+        self.stream.set_nonblocking(true)?;
+        match res {
+            Ok(_) => {
+                log::debug!("Sent {}", message);
+                Ok(())
+            },
+            Err(e) => {
+                self.closed = true;
+                if is_disconnect(&e) {
+                    return Err(IpcError::Closed)
+                }
+                // Part of the line may have been sent, which would garble
+                // every request after it.
+                log::error!("Unable to send {} bytes to mpv, closing the socket: {}", line.len(), e);
+                let _ = self.stream.shutdown(Shutdown::Both);
+                Err(IpcError::Io(e))
+            },
+        }
+    }
+
+    /// Reads everything available on the socket without blocking.
+    fn fill_buffer(&mut self) -> Result<(), IpcError> {
+        let mut buf = [0; 4096];
+        loop {
+            match self.stream.read(&mut buf) {
+                Ok(0) => {
+                    log::info!("mpv closed the socket");
+                    self.closed = true;
+                    return Ok(())
+                },
+                Ok(len) => self.buffer.extend_from_slice(&buf[..len]),
+                Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => return Ok(()),
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => (),
+                Err(e) => {
+                    if is_disconnect(&e) {
+                        self.closed = true;
+                        return Ok(())
+                    }
+                    return Err(IpcError::Io(e))
+                },
+            }
+        }
+    }
+
+    /// Takes the next complete line out of the buffer, skipping the ones
+    /// that aren't JSON.
+    fn next_message(&mut self) -> Option<Value> {
+        while let Some(end) = self.buffer.iter().position(|&byte| byte == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+            match serde_json::from_slice(&line) {
+                Ok(message) => return Some(message),
+                Err(e) => log::error!("Invalid message from mpv ({}): {}", e, String::from_utf8_lossy(&line).trim_end()),
+            }
+        }
+        None
+    }
+
+    fn push_event(&mut self, message: Value) {
+        match serde_json::from_value::<MpvEvent>(message) {
+            Ok(event) => {
+                if event.event == "shutdown" {
+                    self.closed = true;
+                }
+                self.events.push_back(event);
+            },
+            Err(e) => log::error!("Invalid event from mpv: {}", e),
+        }
+    }
+}
+
+fn is_disconnect(e: &io::Error) -> bool {
+    [io::ErrorKind::BrokenPipe, io::ErrorKind::ConnectionReset, io::ErrorKind::ConnectionAborted].contains(&e.kind())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::net::UnixListener;
+    use std::io::{
+        BufRead,
+        BufReader,
+    };
+    use std::path::PathBuf;
+
+    /// Socket path unique to a test.
+    fn socket_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("invidious-tui-{}-{}.sock", std::process::id(), name));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    /// Fake mpv: accepts one client and hands each request line to `respond`,
+    /// writing back whatever it returns. The connection is dropped when the
+    /// client hangs up or `respond` returns `None`.
+    fn fake_mpv<F>(path: &Path, mut respond: F) -> thread::JoinHandle<()>
+    where F: FnMut(Value) -> Option<Vec<String>> + Send + 'static
+    {
+        let listener = UnixListener::bind(path).unwrap();
+        thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut writer = stream.try_clone().unwrap();
+            for line in BufReader::new(stream).lines() {
+                let request: Value = match line {
+                    Ok(line) => serde_json::from_str(&line).unwrap(),
+                    Err(_) => break,
+                };
+                match respond(request) {
+                    Some(chunks) => {
+                        for chunk in chunks {
+                            writer.write_all(chunk.as_bytes()).unwrap();
+                            writer.flush().unwrap();
+                            thread::sleep(Duration::from_millis(20));
+                        }
+                    },
+                    None => break,
+                }
+            }
+        })
+    }
+
+    fn reply(request: &Value, data: Value) -> String {
+        format!("{}\n", json!({ "data": data, "request_id": request["request_id"], "error": "success" }))
+    }
+
+    #[test]
+    fn get_property_matches_reply_by_request_id() {
+        let path = socket_path("request-id");
+        let server = fake_mpv(&path, |request| {
+            if request["command"][0] == "get_property" {
+                // An event and a stale reply arrive before the answer.
+                let event = "{\"event\":\"property-change\",\"id\":1,\"name\":\"playlist-pos\",\"data\":2}\n";
+                let stale = json!({ "data": true, "request_id": 999, "error": "success" });
+                Some(vec![String::from(event), format!("{}\n", stale), reply(&request, json!(false))])
+            }
+            else {
+                Some(vec![reply(&request, Value::Null)])
+            }
+        });
+
+        let mut client = IpcClient::connect(&path, Duration::from_secs(1)).unwrap();
+        client.set_property("volume", json!(50)).unwrap();
+        let paused: bool = client.get_property("pause").unwrap();
+        assert!(!paused);
+
+        let events = client.poll_events().unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].name.as_deref(), Some("playlist-pos"));
+        assert_eq!(events[0].data, json!(2));
+
+        drop(client);
+        server.join().unwrap();
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn mpv_errors_are_returned() {
+        let path = socket_path("error");
+        let server = fake_mpv(&path, |request| {
+            let error = json!({ "request_id": request["request_id"], "error": "property not found" });
+            Some(vec![format!("{}\n", error)])
+        });
+
+        let mut client = IpcClient::connect(&path, Duration::from_secs(1)).unwrap();
+        match client.get_property::<bool>("nope") {
+            Err(IpcError::Mpv(e)) => assert_eq!(e, "property not found"),
+            res => panic!("unexpected result: {:?}", res.map(|_| ())),
+        }
+
+        drop(client);
+        server.join().unwrap();
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn events_split_across_reads_are_framed() {
+        let path = socket_path("framing");
+        let server = fake_mpv(&path, |request| {
+            let event = "{\"event\":\"property-change\",\"id\":2,\"name\":\"time-pos\",\"data\":12.5}\n";
+            let (start, end) = event.split_at(20);
+            Some(vec![
+                reply(&request, Value::Null),
+                String::from(start),
+                format!("{}{}", end, "{\"event\":\"pause\"}\n"),
+            ])
+        });
+
+        let mut client = IpcClient::connect(&path, Duration::from_secs(1)).unwrap();
+        client.observe_property(2, "time-pos").unwrap();
+
+        let mut events = vec![];
+        let start = Instant::now();
+        while events.len() < 2 && start.elapsed() < Duration::from_secs(2) {
+            events.extend(client.poll_events().unwrap());
+            thread::sleep(POLL_INTERVAL);
+        }
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].id, Some(2));
+        assert_eq!(events[0].data, json!(12.5));
+        assert_eq!(events[1].event, "pause");
+
+        drop(client);
+        server.join().unwrap();
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn detects_mpv_exiting() {
+        let path = socket_path("exit");
+        let server = fake_mpv(&path, |request| {
+            if request["command"][0] == "quit" {
+                return None
+            }
+            Some(vec![reply(&request, Value::Null)])
+        });
+
+        let mut client = IpcClient::connect(&path, Duration::from_secs(1)).unwrap();
+        client.command(&[json!("quit")]).unwrap();
+        server.join().unwrap();
+
+        let start = Instant::now();
+        loop {
+            match client.poll_events() {
+                Err(IpcError::Closed) => break,
+                Ok(_) => assert!(start.elapsed() < Duration::from_secs(2), "close not detected"),
+                Err(e) => panic!("unexpected error: {}", e),
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+        match client.get_property::<bool>("pause") {
+            Err(IpcError::Closed) => (),
+            res => panic!("unexpected result: {:?}", res.map(|_| ())),
+        }
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn long_commands_are_sent_whole() {
+        const LEN: usize = 1 << 22;
+        let path = socket_path("long");
+        let server = fake_mpv(&path, |request| {
+            if request["command"][0] == "loadfile" {
+                assert_eq!(request["command"][1].as_str().map(str::len), Some(LEN));
+                Some(vec![reply(&request, Value::Null)])
+            }
+            else {
+                Some(vec![reply(&request, json!(true))])
+            }
+        });
+
+        // Far more than the socket buffer holds.
+        let mut client = IpcClient::connect(&path, Duration::from_secs(1)).unwrap();
+        client.command(&[json!("loadfile"), json!("x".repeat(LEN))]).unwrap();
+        let paused: bool = client.get_property("pause").unwrap();
+        assert!(paused);
+
+        drop(client);
+        server.join().unwrap();
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn connect_times_out_without_mpv() {
+        let path = socket_path("timeout");
+        let start = Instant::now();
+        match IpcClient::connect(&path, Duration::from_millis(100)) {
+            Err(IpcError::Timeout) => (),
+            res => panic!("unexpected result: {:?}", res.map(|_| ())),
+        }
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+}
@@ -0,0 +1,983 @@
+pub mod ipc;
+pub mod proxy;
+
+use mpv::{
+    MpvHandler,
+    MpvHandlerBuilder,
+    Event,
+    Format,
+};
+use std::process::Command;
+use failure::Error;
+use crate::{
+    store,
+    config::Quality,
+    queue::ShuffleMode,
+    player::ipc::{
+        IpcClient,
+        IpcError,
+        MpvEvent,
+    },
+    player::proxy::StreamProxy,
+};
+use serde::{
+    Serialize,
+    Deserialize,
+};
+use serde_json::{
+    Value,
+    json,
+};
+use std::{
+    fs,
+    io,
+    process,
+    path::PathBuf,
+    time::{
+        Duration,
+        Instant,
+    },
+    convert::TryInto,
+};
+
+const PLAYER_SETTINGS_FILE: &str = "player.json";
+/// How long mpv gets to open its socket after being launched.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const PLAYLIST_POS_OBSERVER: u64 = 1;
+const TIME_POS_OBSERVER: u64 = 2;
+const DURATION_OBSERVER: u64 = 3;
+const PAUSE_OBSERVER: u64 = 4;
+
+/// Player controlled by the transport keys.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum PlayerTarget {
+    Audio,
+    Video,
+}
+pub const MAX_VOLUME: i64 = 130;
+pub const MIN_SPEED: f64 = 0.25;
+pub const MAX_SPEED: f64 = 4.0;
+
+/// Volume and speed of the last session, shared by both players.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+struct PlayerSettings {
+    volume: i64,
+    speed: f64,
+}
+
+impl PlayerSettings {
+    fn load() -> Self {
+        let settings: PlayerSettings = store::load_state(PLAYER_SETTINGS_FILE);
+        PlayerSettings {
+            volume: limit(settings.volume, 0, MAX_VOLUME),
+            speed: limit(settings.speed, MIN_SPEED, MAX_SPEED),
+        }
+    }
+
+    fn save(&self) {
+        if let Err(e) = store::save_state(PLAYER_SETTINGS_FILE, self) {
+            log::error!("Unable to save player settings: {}", e);
+        }
+    }
+}
+
+/// Socket of the video player, unique to this process so that several
+/// instances can play videos at once: `$XDG_RUNTIME_DIR/invidious-tui-<pid>.sock`,
+/// or in the temporary directory when `XDG_RUNTIME_DIR` is unset.
+fn video_socket_path() -> PathBuf {
+    std::env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .unwrap_or_else(std::env::temp_dir)
+        .join(format!("invidious-tui-{}.sock", process::id()))
+}
+
+/// `seconds` as `HH:MM:SS`.
+fn format_time(seconds: i64) -> String {
+    let hours = seconds / 3600;
+    let seconds = seconds % 3600;
+    let minutes = seconds / 60;
+    let seconds = seconds % 60;
+    format!("{:0>2}:{:0>2}:{:0>2}", hours, minutes, seconds)
+}
+
+/// Whether `loadfile` of this mpv takes an insertion index before the
+/// per-file options, which it does since 0.38. Versions that can't be read
+/// are taken to be recent.
+fn loadfile_takes_index(version: &str) -> bool {
+    let version = version.trim_start_matches("mpv").trim().trim_start_matches('v');
+    let mut numbers = version.split(|c: char| !c.is_ascii_digit()).map(|n| n.parse::<u32>().ok());
+    match (numbers.next().and_then(|n| n), numbers.next().and_then(|n| n)) {
+        (Some(major), Some(minor)) => major > 0 || minor >= 38,
+        _ => true,
+    }
+}
+
+/// `loadfile` arguments with per-file `options` in the place `indexed`
+/// calls for, see `loadfile_takes_index`.
+fn loadfile_args(url: &str, flags: &str, options: &str, indexed: bool) -> Vec<String> {
+    let mut args = vec![String::from("loadfile"), url.to_owned(), flags.to_owned()];
+    if indexed {
+        args.push(String::from("-1"));
+    }
+    args.push(options.to_owned());
+    args
+}
+
+/// `value` kept within `min..=max`.
+fn limit<T: PartialOrd>(value: T, min: T, max: T) -> T {
+    if value < min {
+        min
+    }
+    else if value > max {
+        max
+    }
+    else {
+        value
+    }
+}
+
+impl Default for PlayerSettings {
+    fn default() -> Self {
+        PlayerSettings {
+            volume: 100,
+            speed: 1.0,
+        }
+    }
+}
+
+pub struct Player {
+    audio: MpvHandler,
+    /// Index of the track the audio player started last, as observed in
+    /// order with its other events.
+    audio_started_pos: i64,
+    video: Option<IpcClient>,
+    video_socket: PathBuf,
+    video_playlist_pos: i64,
+    video_time: i64,
+    video_duration: i64,
+    video_paused: bool,
+    /// Whether `loadfile` takes an insertion index, for each player.
+    audio_loadfile_index: bool,
+    video_loadfile_index: bool,
+    target: PlayerTarget,
+    /// youtube-dl format of the video player.
+    video_format: String,
+    proxy: StreamProxy,
+    /// How the audio queue is shuffled, shown with the loop flags.
+    pub shuffle: ShuffleMode,
+    settings: PlayerSettings,
+    muted: bool,
+}
+
+impl Player {
+    pub fn new(quality: &Quality) -> Self {
+        let settings = PlayerSettings::load();
+        let audio = Player::init_audio(&settings, quality).unwrap();
+        let audio_loadfile_index = match audio.get_property::<&str>("mpv-version") {
+            Ok(version) => loadfile_takes_index(version),
+            Err(e) => {
+                log::error!("Unable to get mpv version: {}", e);
+                true
+            },
+        };
+        Player {
+            audio,
+            audio_started_pos: -1,
+            video: None,
+            video_socket: video_socket_path(),
+            video_playlist_pos: -1,
+            video_time: 0,
+            video_duration: 0,
+            video_paused: false,
+            audio_loadfile_index,
+            video_loadfile_index: true,
+            target: PlayerTarget::Audio,
+            video_format: quality.video_format(),
+            proxy: StreamProxy::new(quality),
+            shuffle: ShuffleMode::Off,
+            settings,
+            muted: false,
+        }
+    }
+
+    /// Index of the playing track in the audio playlist, or -1.
+    pub fn audio_playlist_pos(&mut self) -> i64 {
+        let mut failed = vec![];
+        while let Some(event) = self.audio.wait_event(0.0) {
+            match event {
+                Event::StartFile => log::debug!("Audio track started"),
+                Event::PropertyChange { change: Format::Int(pos), reply_userdata, .. } if u64::from(reply_userdata) == PLAYLIST_POS_OBSERVER => {
+                    self.audio_started_pos = pos;
+                },
+                Event::EndFile(Err(e)) => {
+                    log::error!("Unable to play audio track {}: {}", self.audio_started_pos, e);
+                    failed.push(self.audio_started_pos);
+                },
+                _ => (),
+            }
+        }
+        for index in failed {
+            self.play_fallback(false, index);
+        }
+        self.audio.get_property::<i64>("playlist-pos").unwrap_or(-1)
+    }
+
+    /// Index of the playing video in the video playlist, or -1, following the
+    /// `playlist-pos` changes observed on the video socket. Also keeps track
+    /// of `time-pos` for `video_time`.
+    pub fn video_playlist_pos(&mut self) -> i64 {
+        let res = match self.video.as_mut() {
+            Some(video) => video.poll_events(),
+            None => return self.video_playlist_pos,
+        };
+        match res {
+            Ok(events) => {
+                for event in events {
+                    self.handle_video_event(event);
+                }
+            },
+            Err(IpcError::Closed) => self.video_closed(),
+            Err(e) => log::error!("Unable to read video player events: {}", e),
+        }
+        self.video_playlist_pos
+    }
+
+    fn handle_video_event(&mut self, event: MpvEvent) {
+        if event.event == "end-file" && event.reason.as_deref() == Some("error") {
+            log::error!("Unable to play video {}", self.video_playlist_pos);
+            self.play_fallback(true, self.video_playlist_pos);
+            return
+        }
+        if event.event != "property-change" {
+            log::debug!("Video event: {}", event.event);
+            return
+        }
+        match event.id {
+            Some(TIME_POS_OBSERVER) => self.video_time = event.data.as_f64().unwrap_or_default() as i64,
+            Some(DURATION_OBSERVER) => self.video_duration = event.data.as_f64().unwrap_or_default() as i64,
+            Some(PAUSE_OBSERVER) => self.video_paused = event.data.as_bool().unwrap_or_default(),
+            Some(PLAYLIST_POS_OBSERVER) => {
+                log::debug!("Video playlist-pos = {}", event.data);
+                self.video_playlist_pos = event.data.as_i64().unwrap_or(-1);
+            },
+            _ => (),
+        }
+    }
+
+    /// Removes the socket left by a previous video player, if any.
+    fn remove_video_socket(&self) {
+        if let Err(e) = fs::remove_file(&self.video_socket) {
+            if e.kind() != io::ErrorKind::NotFound {
+                log::error!("Unable to remove {}: {}", self.video_socket.display(), e);
+            }
+        }
+    }
+
+    /// Whether the video player is running. It exits at the end of its
+    /// playlist or when its window is closed.
+    pub fn has_video(&mut self) -> bool {
+        self.video_playlist_pos();
+        self.video.is_some()
+    }
+
+    fn video_closed(&mut self) {
+        log::info!("Video player closed");
+        self.video = None;
+        self.video_playlist_pos = -1;
+    }
+
+    pub fn get_percent_pos(&self) -> u16 {
+        match self.audio.get_property::<i64>("percent-pos") {
+            Ok(percent) => return percent.try_into().unwrap_or(0),
+            Err(e) => log::error!("Unable to get percent position: {}", e),
+        }
+        0
+    }
+
+    /// Url of the track loaded in the audio player, if any.
+    pub fn audio_path(&self) -> Option<String> {
+        self.audio.get_property::<&str>("path")
+            .ok()
+            .map(String::from)
+    }
+
+    pub fn seek_audio(&mut self, seek_amnt: &str) {
+        match self.audio.command(&["seek", seek_amnt]) {
+            Ok(_) => log::info!("Successfully moved {} seconds", seek_amnt),
+            Err(e) => log::error!("Unable to seek audio: {}", e),
+        }
+    }
+    
+    /// Seconds played of the track loaded in the audio player.
+    pub fn playback_time(&self) -> Option<i64> {
+        self.audio.get_property::<i64>("playback-time").ok()
+    }
+
+    /// Seconds played of the video in the video player.
+    pub fn video_time(&self) -> i64 {
+        self.video_time
+    }
+
+    /// Switches the player controlled by the transport keys.
+    pub fn toggle_target(&mut self) -> PlayerTarget {
+        self.target = match self.target {
+            PlayerTarget::Audio => PlayerTarget::Video,
+            PlayerTarget::Video => PlayerTarget::Audio,
+        };
+        self.target
+    }
+
+    /// Whether the transport keys act on the video player: it is targeted
+    /// and open. Otherwise they act on the audio player.
+    pub fn controls_video(&self) -> bool {
+        self.target == PlayerTarget::Video && self.video.is_some()
+    }
+
+    pub fn toggle_playback(&mut self) {
+        if self.controls_video() {
+            self.toggle_video_playback();
+        }
+        else {
+            self.toggle_audio_playback();
+        }
+    }
+
+    /// Seeks the controlled player by `seconds`, backwards when negative.
+    pub fn seek(&mut self, seconds: i64) {
+        if self.controls_video() {
+            self.video_command(&[json!("seek"), json!(seconds)]);
+        }
+        else {
+            self.seek_audio(&seconds.to_string());
+        }
+    }
+
+    pub fn playlist_next(&mut self) {
+        let is_video = self.controls_video();
+        self.playlist_command(is_video, &["playlist-next"]);
+    }
+
+    pub fn playlist_prev(&mut self) {
+        let is_video = self.controls_video();
+        self.playlist_command(is_video, &["playlist-prev"]);
+    }
+
+    pub fn stop(&mut self) {
+        if self.controls_video() {
+            self.stop_video();
+        }
+        else {
+            self.stop_audio();
+        }
+    }
+
+    /// Toggles fullscreen in the video player. Returns false when no video
+    /// is open.
+    pub fn toggle_fullscreen(&mut self) -> bool {
+        if self.video.is_none() {
+            return false
+        }
+        self.video_command(&[json!("cycle"), json!("fullscreen")]);
+        true
+    }
+
+    /// Plays watch urls of `instance` from its proxied streams from now on.
+    pub fn enable_proxy(&mut self, instance: &str) {
+        self.proxy.enable(instance);
+    }
+
+    pub fn play(&mut self, url: String, is_video: bool) {
+        self.play_from(url, is_video, None);
+    }
+
+    /// Like `play`, but starts `start` seconds in.
+    pub fn play_from(&mut self, url: String, is_video: bool, start: Option<i64>) {
+        let streams = self.proxy.resolve(url, is_video);
+        if is_video {
+            let mut args = vec![];
+            if let Some(start) = start {
+                args.push(format!("--start={}", start));
+            }
+            if let Some(audio_url) = streams.audio_url {
+                args.push(format!("--audio-file={}", audio_url));
+            }
+            args.push(streams.url);
+            self.spawn_video(args);
+        }
+        else {
+            self.pause_video();
+            
+            let url = streams.url;
+            let res = match start {
+                Some(start) => {
+                    let args = loadfile_args(&url, "replace", &format!("start={}", start), self.audio_loadfile_index);
+                    self.audio.command(&args.iter().map(String::as_str).collect::<Vec<_>>())
+                },
+                None => self.audio.command(&["loadfile", url.as_str()]),
+            };
+            match res {
+                Ok(_) => log::info!("Succesfully launched player. Playing {}", url),
+                Err(e) => log::error!("Error loading {}: {}", url, e),
+            }
+            self.resume_audio();
+        }
+    }
+    
+    /// Plays a stream url from the formats of a video, with a separate
+    /// `audio_url` for video-only streams.
+    pub fn play_stream(&mut self, url: String, audio_url: Option<String>, is_video: bool) {
+        if is_video {
+            let mut args = vec![];
+            if let Some(audio_url) = audio_url {
+                args.push(format!("--audio-file={}", audio_url));
+            }
+            args.push(url);
+            self.spawn_video(args);
+        }
+        else {
+            self.play(url, false);
+        }
+    }
+
+    /// Loads a whole playlist, starting `start` seconds into the track at
+    /// `current`.
+    pub fn load_playlist(&mut self, is_video: bool, urls: Vec<String>, current: usize, start: i64) {
+        if is_video {
+            let mut args = vec![format!("--playlist-start={}", current)];
+            for (i, url) in urls.into_iter().enumerate() {
+                let streams = self.proxy.resolve(url, true);
+                // Options between --{ and --} only apply to that file.
+                let mut options = vec![];
+                if i == current && start > 0 {
+                    options.push(format!("--start={}", start));
+                }
+                if let Some(audio_url) = streams.audio_url {
+                    options.push(format!("--audio-file={}", audio_url));
+                }
+                if options.is_empty() {
+                    args.push(streams.url);
+                }
+                else {
+                    args.push(String::from("--{"));
+                    args.extend(options);
+                    args.extend(vec![streams.url, String::from("--}")]);
+                }
+            }
+            self.spawn_video(args);
+            return
+        }
+
+        self.pause_video();
+        let start = format!("start={}", start);
+        let urls: Vec<String> = urls
+            .into_iter()
+            .map(|url| self.proxy.resolve(url, false).url)
+            .collect();
+        for (i, url) in urls.iter().enumerate() {
+            let res = if i == current {
+                let args = loadfile_args(url, "append", &start, self.audio_loadfile_index);
+                self.audio.command(&args.iter().map(String::as_str).collect::<Vec<_>>())
+            }
+            else {
+                self.audio.command(&["loadfile", url.as_str(), "append"])
+            };
+            if let Err(e) = res {
+                log::error!("Error loading {}: {}", url, e);
+            }
+        }
+        self.playlist_play_index(false, current);
+        self.resume_audio();
+    }
+
+    /// Launches mpv for videos with `args` after the default options and
+    /// connects to its socket. A video player that is already open is closed,
+    /// since it would lose its socket to the new one.
+    fn spawn_video(&mut self, args: Vec<String>) {
+        self.pause_audio();
+        if self.video.is_some() {
+            self.video_command(&[json!("quit")]);
+            self.video_closed();
+        }
+        self.remove_video_socket();
+
+        let mut mpv_args = vec![
+            format!("--input-ipc-server={}", self.video_socket.display()),
+            format!("--ytdl-format={}", self.video_format),
+            String::from("--no-terminal"),
+            format!("--volume={}", self.settings.volume),
+            format!("--speed={}", self.settings.speed),
+            format!("--mute={}", if self.muted { "yes" } else { "no" }),
+        ];
+        mpv_args.extend(args);
+
+        let mut child = match Command::new("mpv").args(&mpv_args).spawn() {
+            Ok(child) => {
+                log::info!("Succesfully launched player with {:?}", mpv_args);
+                child
+            },
+            Err(e) => {
+                log::error!("Error spawning mpv: {}", e);
+                return
+            },
+        };
+
+        // Connect in short attempts to notice mpv exiting before it opens
+        // its socket, e.g. on invalid options.
+        let start = Instant::now();
+        let mut video = loop {
+            match IpcClient::connect(&self.video_socket, Duration::from_millis(100)) {
+                Ok(video) => break video,
+                Err(e) => {
+                    if let Ok(Some(status)) = child.try_wait() {
+                        log::error!("mpv exited before opening {} ({})", self.video_socket.display(), status);
+                        return
+                    }
+                    if start.elapsed() >= CONNECT_TIMEOUT {
+                        log::error!("Unable to connect to {}: {}", self.video_socket.display(), e);
+                        return
+                    }
+                },
+            }
+        };
+        match video.get_property::<String>("mpv-version") {
+            Ok(version) => {
+                log::info!("Connected to {} ({})", self.video_socket.display(), version);
+                self.video_loadfile_index = loadfile_takes_index(&version);
+            },
+            Err(e) => {
+                log::error!("Unable to get mpv version from {}: {}", self.video_socket.display(), e);
+                self.video_loadfile_index = true;
+            },
+        }
+
+        let res = video.observe_property(PLAYLIST_POS_OBSERVER, "playlist-pos")
+            .and_then(|_| video.observe_property(TIME_POS_OBSERVER, "time-pos"))
+            .and_then(|_| video.observe_property(DURATION_OBSERVER, "duration"))
+            .and_then(|_| video.observe_property(PAUSE_OBSERVER, "pause"));
+        if let Err(e) = res {
+            log::error!("Unable to observe video properties: {}", e);
+        }
+        self.video = Some(video);
+        self.video_playlist_pos = -1;
+        self.video_time = 0;
+        self.video_duration = 0;
+        self.video_paused = false;
+        self.target = PlayerTarget::Video;
+    }
+
+    pub fn volume(&self) -> i64 {
+        self.settings.volume
+    }
+
+    pub fn speed(&self) -> f64 {
+        self.settings.speed
+    }
+
+    pub fn is_muted(&self) -> bool {
+        self.muted
+    }
+
+    /// Sets the volume of both players, in percent up to `MAX_VOLUME`.
+    pub fn set_volume(&mut self, volume: i64) {
+        self.settings.volume = limit(volume, 0, MAX_VOLUME);
+        self.set_player_property("volume", json!(self.settings.volume));
+        self.settings.save();
+    }
+
+    pub fn change_volume(&mut self, delta: i64) {
+        self.set_volume(self.settings.volume + delta);
+    }
+
+    pub fn toggle_mute(&mut self) {
+        self.muted = !self.muted;
+        self.set_player_property("mute", json!(self.muted));
+    }
+
+    /// Sets the playback speed of both players, between `MIN_SPEED` and
+    /// `MAX_SPEED`.
+    pub fn set_speed(&mut self, speed: f64) {
+        let speed = (speed * 100.0).round() / 100.0;
+        self.settings.speed = limit(speed, MIN_SPEED, MAX_SPEED);
+        self.set_player_property("speed", json!(self.settings.speed));
+        self.settings.save();
+    }
+
+    pub fn change_speed(&mut self, delta: f64) {
+        self.set_speed(self.settings.speed + delta);
+    }
+
+    fn set_player_property(&mut self, name: &str, value: Value) {
+        let res = match &value {
+            Value::Bool(value) => self.audio.set_property(name, *value),
+            Value::Number(number) if number.is_i64() => self.audio.set_property(name, number.as_i64().unwrap_or_default()),
+            Value::Number(number) => self.audio.set_property(name, number.as_f64().unwrap_or_default()),
+            _ => return,
+        };
+        if let Err(e) = res {
+            log::error!("Unable to set {} to {}: {}", name, value, e);
+        }
+        let res = match self.video.as_mut() {
+            Some(video) => video.set_property(name, value),
+            None => return,
+        };
+        self.check_video_result(res);
+    }
+
+    pub fn toggle_loop_audio(&mut self) {
+        match self.audio.get_property::<&str>("loop") {
+            Ok(loop_value) => {
+                if loop_value == "no" {
+                    self.audio.set_option("loop", "inf").unwrap();
+                }
+                else {
+                    self.audio.set_option("loop", "no").unwrap();
+                }
+            },
+            Err(e) => log::error!("Unable to toggle loop: {}", e),
+        }
+    }
+
+    pub fn toggle_loop_playlist_audio(&mut self) {
+        match self.audio.get_property::<&str>("loop-playlist") {
+            Ok(loop_value) => {
+                if loop_value == "no" {
+                    self.audio.set_option("loop-playlist", "inf").unwrap();
+                }
+                else {
+                    self.audio.set_option("loop-playlist", "no").unwrap();
+                }
+            },
+            Err(e) => log::error!("Unable to toggle loop: {}", e),
+        }
+    }
+    
+    pub fn stop_video(&mut self) {
+        self.video_command(&[json!("stop")]);
+        self.video = None;
+        self.video_playlist_pos = -1;
+    }
+
+    pub fn stop_audio(&mut self) {
+        match self.audio.command(&["stop"]) {
+            Ok(_) => log::info!("Successfully stopped audio"),
+            Err(e) => log::error!("Unable to stop audio: {}", e),
+        }
+    }
+
+    pub fn stop_all(&mut self) {
+        self.stop_audio();
+        self.stop_video()
+    }
+
+    pub fn pause_video(&mut self) {
+        self.set_video_pause(true);
+    }
+
+    pub fn pause_audio(&mut self) {
+        match self.audio.set_property("pause", true) {
+            Ok(_) => log::info!("Paused audio"),
+            Err(e) => log::error!("Unable to pause audio: {}", e)
+        }
+    }
+
+    pub fn resume_video(&mut self) {
+        self.set_video_pause(false);
+    }
+
+    fn set_video_pause(&mut self, pause: bool) {
+        let res = match self.video.as_mut() {
+            Some(video) => video.set_property("pause", json!(pause)),
+            None => return,
+        };
+        self.check_video_result(res);
+    }
+
+    pub fn resume_audio(&mut self) {
+        match self.audio.set_property("pause", false) {
+            Ok(_) => log::info!("Resumed audio"),
+            Err(e) => log::error!("Unable to resume audio: {}", e),
+        }
+    }
+
+    pub fn toggle_audio_playback(&mut self) {
+        match self.audio.get_property("pause") {
+            Ok(pause) => {
+                if pause {
+                    if self.video.is_some() {
+                        self.pause_video();
+                    }
+                    
+                    self.resume_audio();
+                }
+                else {
+                    self.pause_audio();
+                }
+            },
+            Err(e) => log::error!("Unable to get pause state: {}", e),
+        }
+    }
+
+    pub fn toggle_video_playback(&mut self) {
+        let res = match self.video.as_mut() {
+            Some(video) => video.get_property::<bool>("pause"),
+            None => return,
+        };
+        match res {
+            Ok(true) => {
+                self.pause_audio();
+                self.resume_video();
+            },
+            Ok(false) => self.pause_video(),
+            Err(IpcError::Closed) => self.video_closed(),
+            Err(e) => log::error!("Unable to get video pause state: {}", e),
+        }
+    }
+
+    pub fn queue_audio(&mut self, url: String) {
+        let url = self.proxy.resolve(url, false).url;
+        match self.audio.command(&["loadfile", url.as_str(), "append-play"]) {
+            Ok(_) => log::info!("Succesfully queued track {}", url),
+            Err(e) => log::error!("Unable to queue track {}: {}", url, e),
+        }
+    }
+    
+    pub fn queue_video(&mut self, url: String) {
+        let streams = self.proxy.resolve(url, true);
+        match streams.audio_url {
+            // %length% quotes the url, which may contain any character.
+            Some(audio_url) => {
+                let options = format!("audio-file=%{}%{}", audio_url.len(), audio_url);
+                let args: Vec<Value> = loadfile_args(&streams.url, "append-play", &options, self.video_loadfile_index)
+                    .into_iter()
+                    .map(Value::from)
+                    .collect();
+                self.video_command(&args);
+            },
+            None => self.video_command(&[json!("loadfile"), json!(streams.url), json!("append-play")]),
+        }
+    }
+
+    /// Moves a track of the audio or video playlist to `to`, which is the
+    /// index of the track it is placed before.
+    pub fn playlist_move(&mut self, is_video: bool, from: usize, to: usize) {
+        self.playlist_command(is_video, &["playlist-move", &from.to_string(), &to.to_string()]);
+    }
+
+    pub fn playlist_remove(&mut self, is_video: bool, index: usize) {
+        self.playlist_command(is_video, &["playlist-remove", &index.to_string()]);
+    }
+
+    pub fn playlist_play_index(&mut self, is_video: bool, index: usize) {
+        self.playlist_command(is_video, &["playlist-play-index", &index.to_string()]);
+    }
+
+    /// Removes every track but the playing one.
+    pub fn playlist_clear(&mut self, is_video: bool) {
+        self.playlist_command(is_video, &["playlist-clear"]);
+    }
+
+    /// Replaces a proxied stream at `index` of a playlist that failed to load
+    /// with the watch url behind it, and plays that through youtube-dl.
+    fn play_fallback(&mut self, is_video: bool, index: i64) {
+        let (filename, count) = match self.playlist_entry(is_video, index) {
+            Some(entry) => entry,
+            None => return,
+        };
+        let url = match self.proxy.fallback(&filename) {
+            Some(url) => url,
+            None => return,
+        };
+        log::info!("Falling back to {} for {}", url, filename);
+        // Appended, then moved in front of the failed stream.
+        self.playlist_command(is_video, &["loadfile", &url, "append"]);
+        self.playlist_command(is_video, &["playlist-move", &count.to_string(), &index.to_string()]);
+        self.playlist_command(is_video, &["playlist-remove", &(index + 1).to_string()]);
+        self.playlist_command(is_video, &["playlist-play-index", &index.to_string()]);
+    }
+
+    /// Filename of the track at `index` of a playlist, and the length of the
+    /// playlist.
+    fn playlist_entry(&mut self, is_video: bool, index: i64) -> Option<(String, i64)> {
+        if index < 0 {
+            return None
+        }
+        let filename = format!("playlist/{}/filename", index);
+        if is_video {
+            let res = match self.video.as_mut() {
+                Some(video) => video.get_property::<String>(&filename)
+                    .and_then(|filename| Ok((filename, video.get_property::<i64>("playlist-count")?))),
+                None => return None,
+            };
+            match res {
+                Ok(entry) => Some(entry),
+                Err(IpcError::Closed) => {
+                    self.video_closed();
+                    None
+                },
+                Err(e) => {
+                    log::error!("Unable to get video playlist entry {}: {}", index, e);
+                    None
+                },
+            }
+        }
+        else {
+            let res = self.audio.get_property::<&str>(&filename)
+                .and_then(|filename| Ok((filename.to_owned(), self.audio.get_property::<i64>("playlist-count")?)));
+            match res {
+                Ok(entry) => Some(entry),
+                Err(e) => {
+                    log::error!("Unable to get audio playlist entry {}: {}", index, e);
+                    None
+                },
+            }
+        }
+    }
+
+    fn playlist_command(&mut self, is_video: bool, args: &[&str]) {
+        if is_video {
+            let args: Vec<Value> = args.iter().map(|arg| json!(arg)).collect();
+            self.video_command(&args);
+        }
+        else {
+            match self.audio.command(args) {
+                Ok(_) => log::info!("Sent command: {:?}", args),
+                Err(e) => log::error!("Unable to send command {:?}: {}", args, e),
+            }
+        }
+    }
+
+    pub fn get_status(&self) -> String {
+        match self.audio.get_property::<&str>("idle-active") {
+            Ok(is_idle) => {
+                if is_idle == "yes"{
+                    return String::from("Idle")
+                }
+                match self.audio.get_property::<bool>("pause") {
+                    Ok(is_paused) => { 
+                        if is_paused {
+                            return String::from("Paused") 
+                        }
+                        
+                        let is_looped = match self.audio.get_property::<&str>("loop") {
+                            Ok(loop_value) => {
+                                if loop_value != "no" {
+                                    "LT"
+                                }
+                                else {
+                                    ""
+                                }
+                            },
+                            Err(e) => {
+                                log::error!("Unable to toggle loop: {}", e);
+                                ""
+                            },
+                        };
+                        let is_looped_playlist = match self.audio.get_property::<&str>("loop-playlist") {
+                            Ok(loop_value) => {
+                                if loop_value != "no" {
+                                    "LP"
+                                }
+                                else {
+                                    ""
+                                }
+                            },
+                            Err(e) => {
+                                log::error!("Unable to toggle loop playlist: {}", e);
+                                ""
+                            },
+                        };
+
+                        let flags: Vec<&str> = vec![is_looped_playlist, is_looped, self.shuffle.flag().unwrap_or_default()]
+                            .into_iter()
+                            .filter(|flag| !flag.is_empty())
+                            .collect();
+                        if flags.is_empty() {
+                            String::from("Playing")
+                        }
+                        else {
+                            format!("Playing ({})", flags.join(" | "))
+                        }
+                    },
+                    Err(_) => String::from("Idle"),
+                }
+            },
+            Err(_) => String::from("Idle")
+        }
+    }
+
+    pub fn get_time(&self) -> String {
+        let time = self.audio.get_property::<i64>("playback-time").unwrap_or(0);
+        let duration = self.audio.get_property::<i64>("duration").unwrap_or(0);
+        format!("{} / {}", format_time(time), format_time(duration))
+    }
+
+    pub fn get_video_status(&self) -> String {
+        if self.video.is_none() {
+            String::from("Idle")
+        }
+        else if self.video_paused {
+            String::from("Paused")
+        }
+        else {
+            String::from("Playing")
+        }
+    }
+
+    pub fn get_video_percent_pos(&self) -> u16 {
+        if self.video.is_none() || self.video_duration <= 0 {
+            return 0
+        }
+        limit(self.video_time * 100 / self.video_duration, 0, 100) as u16
+    }
+
+    pub fn get_video_time(&self) -> String {
+        if self.video.is_none() {
+            return format!("{} / {}", format_time(0), format_time(0))
+        }
+        format!("{} / {}", format_time(self.video_time), format_time(self.video_duration))
+    }
+
+    fn init_audio(settings: &PlayerSettings, quality: &Quality) -> Result<MpvHandler, Error> {
+        let mut mpv_builder = MpvHandlerBuilder::new()?;
+        mpv_builder.set_option("osc", true)?;
+        mpv_builder.set_option("sid", "no")?;
+        mpv_builder.set_option("ytdl-format", quality.audio_format().as_str())?;
+        mpv_builder.set_option("video", "no")?;
+        mpv_builder.set_option("x11-netwm", "yes")?;
+
+        let mut mpv = mpv_builder.build()?;
+        mpv.set_property("speed", settings.speed)?;
+        mpv.set_property("volume", settings.volume)?;
+        // Followed in order with the other events, to tell which track
+        // failed to load.
+        mpv.observe_property::<i64>("playlist-pos", PLAYLIST_POS_OBSERVER as u32)?;
+        Ok(mpv)
+    }
+
+    fn video_command(&mut self, args: &[Value]) {
+        let res = match self.video.as_mut() {
+            Some(video) => video.command(args).map(|_| ()),
+            None => return,
+        };
+        self.check_video_result(res);
+    }
+
+    /// Forgets the video player if the socket was closed, logging any other
+    /// error.
+    fn check_video_result(&mut self, res: Result<(), IpcError>) {
+        match res {
+            Ok(_) => (),
+            Err(IpcError::Closed) => self.video_closed(),
+            Err(e) => log::error!("Unable to send video command: {}", e),
+        }
+    }
+
+}
+
+impl Drop for Player {
+    fn drop(&mut self) {
+        self.remove_video_socket();
+    }
+}
@@ -0,0 +1,80 @@
+use crate::{
+    config::Quality,
+    utils,
+};
+
+/// Plays videos of instances that proxy their streams without youtube-dl,
+/// by turning their watch urls into `latest_version` urls of the streams.
+#[derive(Debug)]
+pub struct StreamProxy {
+    /// Instances that were checked to proxy streams.
+    instances: Vec<String>,
+    video_itag: u32,
+    audio_itag: u32,
+}
+
+/// What mpv loads for a url: a stream, and for proxied videos the separate
+/// audio stream to play along.
+#[derive(Debug)]
+pub struct Streams {
+    pub url: String,
+    pub audio_url: Option<String>,
+}
+
+impl StreamProxy {
+    pub fn new(quality: &Quality) -> Self {
+        StreamProxy {
+            instances: vec![],
+            video_itag: quality.video_itag(),
+            audio_itag: quality.audio_itag(),
+        }
+    }
+
+    pub fn enable(&mut self, instance: &str) {
+        if !self.instances.iter().any(|enabled| enabled == instance) {
+            self.instances.push(instance.to_owned());
+        }
+    }
+
+    /// Streams of a watch url of a proxying instance. Other urls are left as
+    /// they are, for youtube-dl to resolve.
+    pub fn resolve(&self, url: String, is_video: bool) -> Streams {
+        let instance = self.instances
+            .iter()
+            .find(|instance| url.starts_with(&format!("{}/watch?", instance)));
+        let (instance, video_id) = match (instance, utils::video_id_from_url(&url)) {
+            (Some(instance), Some(video_id)) => (instance, video_id),
+            _ => return Streams { url, audio_url: None },
+        };
+
+        let audio_url = stream_url(instance, &video_id, self.audio_itag);
+        if is_video {
+            Streams {
+                url: stream_url(instance, &video_id, self.video_itag),
+                audio_url: Some(audio_url),
+            }
+        }
+        else {
+            Streams {
+                url: audio_url,
+                audio_url: None,
+            }
+        }
+    }
+
+    /// The watch url behind a stream of `resolve`, for youtube-dl to play
+    /// when the instance rejects the stream, e.g. for an itag the video
+    /// doesn't have.
+    pub fn fallback(&self, url: &str) -> Option<String> {
+        let instance = self.instances
+            .iter()
+            .find(|instance| url.starts_with(&format!("{}/latest_version?", instance)))?;
+        let video_id = utils::video_id_from_url(url)?;
+        Some(format!("{}/watch?v={}", instance, video_id))
+    }
+}
+
+/// Url of a stream of a video, proxied through `instance`.
+fn stream_url(instance: &str, video_id: &str, itag: u32) -> String {
+    format!("{}/latest_version?id={}&itag={}&local=true", instance, video_id, itag)
+}
@@ -0,0 +1,173 @@
+use serde::{
+    Serialize,
+    Deserialize,
+};
+use failure::{
+    Error,
+    format_err,
+};
+use crate::{
+    store,
+    player::Player,
+    invidious::Invidious,
+    media::{
+        Media,
+        video::Video,
+    },
+    utils,
+};
+
+const PLAYLISTS_FILE: &str = "playlists.json";
+
+/// A playlist kept on this machine rather than on an instance.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct LocalPlaylist {
+    pub name: String,
+    pub videos: Vec<Video>,
+}
+
+impl LocalPlaylist {
+    pub fn to_text(&self) -> Vec<String> {
+        let duration: i64 = self.videos.iter().map(|video| video.length_seconds).sum();
+        vec![
+            self.name.clone(),
+            format!("{} videos", self.videos.len()),
+            utils::format_seconds(duration),
+        ]
+    }
+
+    /// Moves a video one place up (`up`) or down, returning its new index.
+    pub fn move_video(&mut self, index: usize, up: bool) -> Option<usize> {
+        let target = if up {
+            index.checked_sub(1)?
+        }
+        else {
+            index + 1
+        };
+        if target >= self.videos.len() {
+            return None
+        }
+        self.videos.swap(index, target);
+        Some(target)
+    }
+}
+
+/// Plays the first video and appends the rest to the mpv playlist.
+impl Media for LocalPlaylist {
+    fn play_video(&self, player: &mut Player, api: &Invidious) {
+        let mut videos = self.videos.iter();
+        if let Some(first) = videos.next() {
+            player.play(api.watch_url(&first.video_id), true);
+        }
+        for video in videos {
+            player.queue_video(api.watch_url(&video.video_id));
+        }
+    }
+
+    fn play_audio(&self, player: &mut Player, api: &Invidious) {
+        let mut videos = self.videos.iter();
+        if let Some(first) = videos.next() {
+            player.play(api.watch_url(&first.video_id), false);
+        }
+        for video in videos {
+            player.queue_audio(api.watch_url(&video.video_id));
+        }
+    }
+
+    fn title(&self) -> String {
+        self.name.clone()
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LocalPlaylists {
+    pub playlists: Vec<LocalPlaylist>,
+}
+
+impl LocalPlaylists {
+    pub fn load() -> Self {
+        store::load(PLAYLISTS_FILE)
+    }
+
+    pub fn save(&self) -> Result<(), Error> {
+        store::save(PLAYLISTS_FILE, self)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&LocalPlaylist> {
+        self.playlists.iter().find(|playlist| playlist.name == name)
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut LocalPlaylist> {
+        self.playlists.iter_mut().find(|playlist| playlist.name == name)
+    }
+
+    pub fn create(&mut self, name: &str) -> Result<(), Error> {
+        if name.is_empty() {
+            return Err(format_err!("Playlist name cannot be empty"))
+        }
+        if self.get(name).is_some() {
+            return Err(format_err!("Playlist {} already exists", name))
+        }
+        self.playlists.push(LocalPlaylist {
+            name: name.to_owned(),
+            videos: vec![],
+        });
+        Ok(())
+    }
+
+    /// Adds a video to the named playlist, creating the playlist if needed.
+    /// Returns false if the video was already in it.
+    pub fn add(&mut self, name: &str, video: Video) -> Result<bool, Error> {
+        if self.get(name).is_none() {
+            self.create(name)?;
+        }
+        let playlist = self.get_mut(name).ok_or_else(|| format_err!("No playlist named {}", name))?;
+        if playlist.videos.iter().any(|v| v.video_id == video.video_id) {
+            return Ok(false)
+        }
+        playlist.videos.push(video);
+        Ok(true)
+    }
+
+    pub fn rename(&mut self, name: &str, new_name: &str) -> Result<(), Error> {
+        if new_name.is_empty() {
+            return Err(format_err!("Playlist name cannot be empty"))
+        }
+        if name != new_name && self.get(new_name).is_some() {
+            return Err(format_err!("Playlist {} already exists", new_name))
+        }
+        let playlist = self.get_mut(name).ok_or_else(|| format_err!("No playlist named {}", name))?;
+        playlist.name = new_name.to_owned();
+        Ok(())
+    }
+
+    pub fn delete(&mut self, name: &str) {
+        self.playlists.retain(|playlist| playlist.name != name);
+    }
+
+    /// Names starting with `prefix`, for completing them on the command line.
+    pub fn names_matching(&self, prefix: &str) -> Vec<String> {
+        let prefix = prefix.to_lowercase();
+        self.playlists
+            .iter()
+            .filter(|playlist| playlist.name.to_lowercase().starts_with(&prefix))
+            .map(|playlist| playlist.name.clone())
+            .collect()
+    }
+
+    pub fn to_text(&self) -> Vec<Vec<String>> {
+        self.playlists
+            .iter()
+            .map(LocalPlaylist::to_text)
+            .collect()
+    }
+}
+
+impl Default for LocalPlaylists {
+    fn default() -> Self {
+        LocalPlaylists {
+            playlists: vec![],
+        }
+    }
+}
@@ -0,0 +1,389 @@
+use serde::{
+    Serialize,
+    Deserialize,
+};
+use failure::Error;
+use rand::{
+    thread_rng,
+    seq::SliceRandom,
+};
+use crate::{
+    store,
+    media::video::Video,
+};
+
+const SAVED_QUEUES_FILE: &str = "queue.json";
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ShuffleMode {
+    Off,
+    /// Shuffled once, then played in that order.
+    Once,
+    /// Shuffled again every time the looped playlist starts over.
+    EachLoop,
+}
+
+impl ShuffleMode {
+    pub fn next(self) -> Self {
+        match self {
+            ShuffleMode::Off => ShuffleMode::Once,
+            ShuffleMode::Once => ShuffleMode::EachLoop,
+            ShuffleMode::EachLoop => ShuffleMode::Off,
+        }
+    }
+
+    /// Flag shown in the player status, next to the loop flags.
+    pub fn flag(self) -> Option<&'static str> {
+        match self {
+            ShuffleMode::Off => None,
+            ShuffleMode::Once => Some("SH"),
+            ShuffleMode::EachLoop => Some("SHL"),
+        }
+    }
+}
+
+/// A track in a queue. Entries are kept in the same order as the playlist of
+/// the mpv instance playing them, so an index means the same in both.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct QueueEntry {
+    pub title: String,
+    pub author: String,
+    pub video_id: String,
+    pub playlist: Option<String>,
+    #[serde(default)]
+    pub author_id: String,
+    #[serde(default)]
+    pub length_seconds: i64,
+    /// Position in the order the entries were queued, to undo shuffling.
+    #[serde(skip)]
+    order: usize,
+}
+
+impl QueueEntry {
+    pub fn new(video: &Video, playlist: Option<String>) -> Self {
+        QueueEntry {
+            title: video.title.clone(),
+            author: video.author.clone(),
+            video_id: video.video_id.clone(),
+            playlist,
+            author_id: video.author_id.clone(),
+            length_seconds: video.length_seconds,
+            order: 0,
+        }
+    }
+
+    /// The video the entry was queued from, as far as the entry knows it.
+    pub fn to_video(&self) -> Video {
+        Video {
+            title: self.title.clone(),
+            author: self.author.clone(),
+            author_id: self.author_id.clone(),
+            video_id: self.video_id.clone(),
+            length_seconds: self.length_seconds,
+            ..Video::default()
+        }
+    }
+
+    fn title(&self) -> String {
+        match &self.playlist {
+            Some(playlist) => format!("[{}] {}", playlist, self.title),
+            None => self.title.clone(),
+        }
+    }
+
+    fn to_text(&self) -> Vec<String> {
+        vec![self.title(), self.author.clone()]
+    }
+}
+
+#[derive(Debug)]
+pub struct Queue {
+    pub entries: Vec<QueueEntry>,
+    /// Index of the playing entry, as reported by mpv's `playlist-pos`.
+    pub current: Option<usize>,
+    queued: usize,
+}
+
+impl Queue {
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Replaces the queue when new media is played.
+    pub fn replace(&mut self, entries: Vec<QueueEntry>) {
+        self.entries.clear();
+        self.current = None;
+        self.append(entries);
+    }
+
+    /// Appends entries that are being loaded into a player. A player that is
+    /// gone took its playlist along, so the queue starts over with them.
+    /// Returns whether the first entry has to start the player.
+    pub fn enqueue(&mut self, entries: Vec<QueueEntry>, player_alive: bool) -> bool {
+        let play_first = !player_alive || self.is_empty();
+        if player_alive {
+            self.append(entries);
+        }
+        else {
+            self.replace(entries);
+        }
+        play_first
+    }
+
+    pub fn append(&mut self, entries: Vec<QueueEntry>) {
+        for mut entry in entries {
+            entry.order = self.queued;
+            self.queued += 1;
+            self.entries.push(entry);
+        }
+    }
+
+    /// Updates the playing entry from mpv's `playlist-pos`, which is -1 when
+    /// nothing is playing. Returns true when a looped playlist started over.
+    pub fn set_position(&mut self, position: i64) -> bool {
+        let previous = self.current;
+        self.current = if position >= 0 && (position as usize) < self.entries.len() {
+            Some(position as usize)
+        }
+        else {
+            None
+        };
+        self.entries.len() > 1 && previous == Some(self.entries.len() - 1) && self.current == Some(0)
+    }
+
+    /// Puts the playing entry first and the rest in random order. Returns the
+    /// `playlist-move` commands that make the same change in mpv.
+    pub fn shuffle(&mut self) -> Vec<(usize, usize)> {
+        let mut order: Vec<usize> = (0..self.entries.len())
+            .filter(|&i| Some(i) != self.current)
+            .collect();
+        order.shuffle(&mut thread_rng());
+        if let Some(current) = self.current {
+            order.insert(0, current);
+        }
+        self.reorder(order)
+    }
+
+    /// Restores the order the entries were queued in.
+    pub fn unshuffle(&mut self) -> Vec<(usize, usize)> {
+        let mut order: Vec<usize> = (0..self.entries.len()).collect();
+        order.sort_by_key(|&i| self.entries[i].order);
+        self.reorder(order)
+    }
+
+    /// Rearranges the entries so the one at `order[i]` ends up at `i`, one
+    /// move at a time like mpv's `playlist-move`, and returns the moves.
+    fn reorder(&mut self, order: Vec<usize>) -> Vec<(usize, usize)> {
+        let mut positions: Vec<usize> = (0..self.entries.len()).collect();
+        let mut moves = vec![];
+        for (i, old) in order.iter().enumerate() {
+            let from = match positions.iter().position(|position| position == old) {
+                Some(from) => from,
+                None => continue,
+            };
+            if from != i {
+                let position = positions.remove(from);
+                positions.insert(i, position);
+                let entry = self.entries.remove(from);
+                self.entries.insert(i, entry);
+                moves.push((from, i));
+            }
+        }
+        self.current = self.current.and_then(|current| positions.iter().position(|&position| position == current));
+        moves
+    }
+
+    /// Whether there is anything left to play after the current entry.
+    pub fn has_next(&self) -> bool {
+        match self.current {
+            Some(current) => current + 1 < self.entries.len(),
+            None => !self.entries.is_empty(),
+        }
+    }
+
+    pub fn remove(&mut self, index: usize) -> Option<QueueEntry> {
+        if index >= self.entries.len() {
+            return None
+        }
+        if let Some(current) = self.current {
+            if index < current {
+                self.current = Some(current - 1);
+            }
+        }
+        Some(self.entries.remove(index))
+    }
+
+    /// Moves an entry one place up (`up`) or down, returning its new index.
+    pub fn move_entry(&mut self, index: usize, up: bool) -> Option<usize> {
+        let target = if up {
+            index.checked_sub(1)?
+        }
+        else {
+            index + 1
+        };
+        if target >= self.entries.len() {
+            return None
+        }
+        self.entries.swap(index, target);
+        if self.current == Some(index) {
+            self.current = Some(target);
+        }
+        else if self.current == Some(target) {
+            self.current = Some(index);
+        }
+        Some(target)
+    }
+
+    /// Drops everything but the playing entry, like mpv's `playlist-clear`.
+    pub fn clear(&mut self) {
+        match self.current.and_then(|current| self.entries.get(current).cloned()) {
+            Some(entry) => {
+                self.entries = vec![entry];
+                self.current = Some(0);
+            },
+            None => self.entries.clear(),
+        }
+    }
+
+    pub fn current_entry(&self) -> Option<&QueueEntry> {
+        self.current.and_then(|current| self.entries.get(current))
+    }
+
+    pub fn current_text(&self) -> String {
+        entry_text(self.current_entry())
+    }
+
+    pub fn next_text(&self) -> String {
+        let next = match self.current {
+            Some(current) => self.entries.get(current + 1),
+            None => None,
+        };
+        entry_text(next)
+    }
+
+    pub fn to_text(&self) -> Vec<Vec<String>> {
+        self.entries
+            .iter()
+            .map(QueueEntry::to_text)
+            .collect()
+    }
+
+    /// The queue as it is saved, `position` seconds into the current entry.
+    pub fn snapshot(&self, position: i64) -> SavedQueue {
+        SavedQueue {
+            entries: self.entries.clone(),
+            current: self.current,
+            position: if self.current.is_some() { position } else { 0 },
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SavedQueue {
+    pub entries: Vec<QueueEntry>,
+    pub current: Option<usize>,
+    pub position: i64,
+}
+
+impl Default for SavedQueue {
+    fn default() -> Self {
+        SavedQueue {
+            entries: vec![],
+            current: None,
+            position: 0,
+        }
+    }
+}
+
+/// Both queues as they were when last saved, to pick up where the previous
+/// session left off.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct SavedQueues {
+    pub audio: SavedQueue,
+    pub video: SavedQueue,
+}
+
+impl SavedQueues {
+    pub fn load() -> Self {
+        store::load_state(SAVED_QUEUES_FILE)
+    }
+
+    pub fn save(&self) -> Result<(), Error> {
+        store::save_state(SAVED_QUEUES_FILE, self)
+    }
+
+    pub fn len(&self) -> usize {
+        self.audio.entries.len() + self.video.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Default for SavedQueues {
+    fn default() -> Self {
+        SavedQueues {
+            audio: SavedQueue::default(),
+            video: SavedQueue::default(),
+        }
+    }
+}
+
+fn entry_text(entry: Option<&QueueEntry>) -> String {
+    match entry {
+        Some(entry) => format!("{} by {}\n", entry.title(), entry.author),
+        None => String::from("None\n"),
+    }
+}
+
+impl Default for Queue {
+    fn default() -> Self {
+        Queue {
+            entries: vec![],
+            current: None,
+            queued: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries(ids: &[&str]) -> Vec<QueueEntry> {
+        ids.iter()
+            .map(|id| QueueEntry::new(&Video { video_id: (*id).to_owned(), ..Video::default() }, None))
+            .collect()
+    }
+
+    fn ids(queue: &Queue) -> Vec<&str> {
+        queue.entries.iter().map(|entry| entry.video_id.as_str()).collect()
+    }
+
+    #[test]
+    fn enqueue_appends_to_a_running_player() {
+        let mut queue = Queue::default();
+        assert!(queue.enqueue(entries(&["a"]), true));
+        queue.set_position(0);
+        assert!(!queue.enqueue(entries(&["b", "c"]), true));
+        assert_eq!(ids(&queue), vec!["a", "b", "c"]);
+        assert_eq!(queue.current, Some(0));
+    }
+
+    #[test]
+    fn enqueue_after_the_player_exited_starts_over() {
+        let mut queue = Queue::default();
+        queue.enqueue(entries(&["a", "b"]), true);
+        queue.set_position(1);
+
+        // mpv quit at the end of its playlist, taking "a" and "b" with it.
+        assert!(queue.enqueue(entries(&["c", "d"]), false));
+        assert_eq!(ids(&queue), vec!["c", "d"]);
+        assert_eq!(queue.current, None);
+        assert_eq!(queue.snapshot(0).entries.len(), 2);
+    }
+}
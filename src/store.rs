@@ -0,0 +1,131 @@
+use serde::{
+    Serialize,
+    de::DeserializeOwned,
+};
+use failure::{
+    Error,
+    format_err,
+};
+use std::{
+    fs,
+    io::Write,
+    path::PathBuf,
+    os::unix::fs::OpenOptionsExt,
+};
+
+const APP_DIR: &str = "invidious-tui";
+
+/// Directory for persisted data, `$XDG_DATA_HOME/invidious-tui`.
+pub fn data_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|mut dir| {
+        dir.push(APP_DIR);
+        dir
+    })
+}
+
+pub fn data_file(name: &str) -> Option<PathBuf> {
+    data_dir().map(|mut file| {
+        file.push(name);
+        file
+    })
+}
+
+/// Directory for state that is only worth keeping between sessions,
+/// `$XDG_STATE_HOME/invidious-tui` or `~/.local/state/invidious-tui`.
+pub fn state_dir() -> Option<PathBuf> {
+    let dir = std::env::var_os("XDG_STATE_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| dirs::home_dir().map(|home| home.join(".local").join("state")));
+    dir.map(|mut dir| {
+        dir.push(APP_DIR);
+        dir
+    })
+}
+
+/// Loads a JSON file from the data directory. A missing file gives the
+/// default value; an unreadable one is logged and also gives the default so a
+/// corrupt file never keeps the application from starting.
+pub fn load<T: DeserializeOwned + Default>(name: &str) -> T {
+    load_from(data_dir(), name)
+}
+
+/// Like `load`, from the state directory.
+pub fn load_state<T: DeserializeOwned + Default>(name: &str) -> T {
+    load_from(state_dir(), name)
+}
+
+fn load_from<T: DeserializeOwned + Default>(dir: Option<PathBuf>, name: &str) -> T {
+    let file = match dir {
+        Some(dir) => dir.join(name),
+        None => return T::default(),
+    };
+    if !file.exists() {
+        return T::default()
+    }
+
+    let content = match fs::read_to_string(&file) {
+        Ok(content) => content,
+        Err(e) => {
+            log::error!("Unable to read {}: {}", file.display(), e);
+            return T::default()
+        },
+    };
+    match serde_json::from_str(&content) {
+        Ok(value) => value,
+        Err(e) => {
+            log::error!("Unable to parse {}: {}", file.display(), e);
+            T::default()
+        },
+    }
+}
+
+/// Saves a value as JSON in the data directory, replacing the previous file
+/// only once the new one is fully written.
+pub fn save<T: Serialize>(name: &str, value: &T) -> Result<(), Error> {
+    let dir = data_dir().ok_or_else(|| format_err!("Unable to find the data directory"))?;
+    save_to(dir, name, value)
+}
+
+/// Like `save`, in the state directory.
+pub fn save_state<T: Serialize>(name: &str, value: &T) -> Result<(), Error> {
+    let dir = state_dir().ok_or_else(|| format_err!("Unable to find the state directory"))?;
+    save_to(dir, name, value)
+}
+
+fn save_to<T: Serialize>(dir: PathBuf, name: &str, value: &T) -> Result<(), Error> {
+    fs::create_dir_all(&dir)?;
+
+    let file = dir.join(name);
+    let tmp = dir.join(format!(".{}.tmp", name));
+    fs::write(&tmp, serde_json::to_string_pretty(value)?)?;
+    fs::rename(&tmp, &file)?;
+    Ok(())
+}
+
+/// Like `save`, but the file is only readable by the current user, for
+/// values such as access tokens.
+pub fn save_private<T: Serialize>(name: &str, value: &T) -> Result<(), Error> {
+    let dir = data_dir().ok_or_else(|| format_err!("Unable to find the data directory"))?;
+    fs::create_dir_all(&dir)?;
+
+    let file = dir.join(name);
+    let tmp = dir.join(format!(".{}.tmp", name));
+    let _ = fs::remove_file(&tmp);
+    let mut writer = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(&tmp)?;
+    writer.write_all(serde_json::to_string_pretty(value)?.as_bytes())?;
+    fs::rename(&tmp, &file)?;
+    Ok(())
+}
+
+pub fn remove(name: &str) -> Result<(), Error> {
+    let file = data_file(name).ok_or_else(|| format_err!("Unable to find the data directory"))?;
+    if file.exists() {
+        fs::remove_file(file)?;
+    }
+    Ok(())
+}
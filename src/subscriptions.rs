@@ -0,0 +1,193 @@
+use serde::{
+    Serialize,
+    Deserialize,
+};
+use failure::Error;
+use crate::{
+    store,
+    invidious::{
+        Invidious,
+        ApiError,
+    },
+    media::{
+        video::Video,
+        channel::Channel,
+    },
+};
+use std::cmp::Reverse;
+
+const SUBSCRIPTIONS_FILE: &str = "subscriptions.json";
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Subscription {
+    pub author: String,
+    pub author_id: String,
+}
+
+impl Subscription {
+    pub fn to_channel(&self) -> Channel {
+        Channel {
+            author: self.author.clone(),
+            author_id: self.author_id.clone(),
+            ..Channel::default()
+        }
+    }
+}
+
+/// Followed channels and the session the feed was last visited in, which
+/// decides which videos are shown as new. When logged in, the channels mirror
+/// the account's subscriptions instead of the local store.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Subscriptions {
+    pub channels: Vec<Subscription>,
+    pub last_visit: i64,
+    #[serde(skip)]
+    pub synced: bool,
+    /// `last_visit` before the feed was visited in this session.
+    #[serde(skip)]
+    previous_visit: Option<i64>,
+}
+
+impl Subscriptions {
+    pub fn load() -> Self {
+        store::load(SUBSCRIPTIONS_FILE)
+    }
+
+    pub fn save(&self) -> Result<(), Error> {
+        if self.synced {
+            // The channels live on the server, only the visit is kept locally.
+            let mut local = Subscriptions::load();
+            local.last_visit = self.last_visit;
+            return local.save()
+        }
+        store::save(SUBSCRIPTIONS_FILE, self)
+    }
+
+    /// Replaces the channels with the ones of the logged in account.
+    pub fn set_synced(&mut self, mut channels: Vec<Subscription>) {
+        channels.sort_by_key(|channel| channel.author.to_lowercase());
+        self.channels = channels;
+        self.synced = true;
+    }
+
+    /// Records a visit of the feed, once per session so that videos stay new
+    /// until the next one. Returns whether `last_visit` changed.
+    pub fn visit(&mut self) -> bool {
+        if self.previous_visit.is_some() {
+            return false
+        }
+        self.previous_visit = Some(self.last_visit);
+        self.last_visit = chrono::Utc::now().timestamp();
+        true
+    }
+
+    /// Videos published after this are new in this session.
+    pub fn new_since(&self) -> i64 {
+        self.previous_visit.unwrap_or(self.last_visit)
+    }
+
+    pub fn is_subscribed(&self, author_id: &str) -> bool {
+        self.channels
+            .iter()
+            .any(|channel| channel.author_id == author_id)
+    }
+
+    /// Subscribes to the channel, or unsubscribes if already subscribed.
+    /// Returns whether the channel is subscribed afterwards.
+    pub fn toggle(&mut self, author: String, author_id: String) -> bool {
+        if self.is_subscribed(&author_id) {
+            self.channels.retain(|channel| channel.author_id != author_id);
+            false
+        }
+        else {
+            self.channels.push(Subscription {
+                author,
+                author_id,
+            });
+            self.channels.sort_by_key(|channel| channel.author.to_lowercase());
+            true
+        }
+    }
+
+    /// Adds the channels that aren't subscribed yet, returning how many were
+    /// imported and how many were skipped as duplicates.
+    pub fn merge(&mut self, channels: Vec<Subscription>) -> (usize, usize) {
+        let mut imported = 0;
+        let mut skipped = 0;
+        for channel in channels {
+            if self.is_subscribed(&channel.author_id) {
+                skipped += 1;
+            }
+            else {
+                self.channels.push(channel);
+                imported += 1;
+            }
+        }
+        self.channels.sort_by_key(|channel| channel.author.to_lowercase());
+        (imported, skipped)
+    }
+
+    /// Names channels that were imported by id only, using the author of
+    /// their videos. Returns whether any name changed.
+    pub fn fill_names(&mut self, videos: &[Video]) -> bool {
+        let mut changed = false;
+        for channel in self.channels.iter_mut().filter(|channel| channel.author == channel.author_id) {
+            if let Some(video) = videos.iter().find(|video| video.author_id == channel.author_id) {
+                channel.author = video.author.clone();
+                changed = true;
+            }
+        }
+        if changed {
+            self.channels.sort_by_key(|channel| channel.author.to_lowercase());
+        }
+        changed
+    }
+
+    pub fn to_text(&self) -> Vec<Vec<String>> {
+        self.channels
+            .iter()
+            .map(|channel| vec![channel.author.clone(), channel.author_id.clone()])
+            .collect()
+    }
+}
+
+impl Default for Subscriptions {
+    fn default() -> Self {
+        Subscriptions {
+            channels: vec![],
+            last_visit: 0,
+            synced: false,
+            previous_visit: None,
+        }
+    }
+}
+
+/// Latest videos of every channel, newest first. Channels that fail to load
+/// are returned separately so the rest of the feed still shows up.
+pub async fn fetch_feed(api: Invidious, channels: Vec<Subscription>) -> (Vec<Video>, Vec<(String, ApiError)>) {
+    let requests = channels.iter().map(|channel| {
+        let endpoint = format!("channels/videos/{}", channel.author_id);
+        let api = api.clone();
+        async move {
+            let params = vec![
+                ("page", "1"),
+                ("sort_by", "newest"),
+            ];
+            api.videos(params, &endpoint).await
+        }
+    });
+    let results = futures::future::join_all(requests).await;
+
+    let mut feed = vec![];
+    let mut failed = vec![];
+    for (channel, result) in channels.into_iter().zip(results) {
+        match result {
+            Ok(mut videos) => feed.append(&mut videos),
+            Err(e) => failed.push((channel.author, e)),
+        }
+    }
+    feed.sort_by_key(|video| Reverse(video.published));
+    (feed, failed)
+}
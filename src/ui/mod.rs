@@ -37,34 +37,72 @@ use crate::{
         TabState,
         ContentType,
     },
-    app::App,
+    app::{
+        App,
+        Notification,
+    },
     player::Player,
+    invidious::InstanceHealth,
+    input::{
+        LineEditor,
+        Suggestions,
+    },
+    utils,
 };
 use std::{
     convert::AsRef,
-    collections::VecDeque,
+    collections::HashSet,
+    time::Duration,
 };
 
+const NOTIFICATION_TIMEOUT: Duration = Duration::from_secs(8);
+const SPINNER: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
 pub fn draw<B>(terminal: &mut Terminal<B>,
                mut app: &mut App
               ) -> Result<(), Error> 
 where
 B: Backend,
 {
+    let instance = instance_status(&app.api.active_health());
+    let spinner = SPINNER[app.tick % SPINNER.len()];
     terminal.draw(|mut f| {
         let layout = fixed_layout(&f);
         if let Some(root_view) = app.view_list.get(&app.focused_view) {
             if let Some(view) = root_view.get_current_view() {
-                draw_tabs(&mut f, layout[0], &view.tabs);
+                draw_tabs(&mut f, layout[0], &view.tabs, &instance);
                 if let Some(window) = view.root_windows.get(view.tabs.selected) {
-                    draw_table(&mut f, layout[1], window);
+                    match window.content {
+                        ContentType::TextContent(_) => draw_text(&mut f, layout[1], window, spinner),
+                        _ => {
+                            let watched = utils::watched_rows(window, &app.loaded_data);
+                            draw_table(&mut f, layout[1], window, &watched, spinner)
+                        },
+                    }
                 }
             }
         }
 
         draw_player(&mut f, layout[2], &mut app);
-        draw_cmdline(&mut f, layout[3], &app.input);
+        if let Some(notification) = &app.notification {
+            draw_notification(&mut f, layout[3], notification);
+        }
+        match &app.search_history.reverse_search {
+            Some(search) => draw_reverse_search(&mut f, layout[4], &search.pattern, app.search_history.reverse_search_match()),
+            None => draw_cmdline(&mut f, layout[4], &app.input, app.cmdline_focused),
+        }
+        if app.cmdline_focused && !app.suggestions.items.is_empty() {
+            draw_suggestions(&mut f, layout[4], &app.suggestions);
+        }
     })?;
+
+    let expired = app.notification
+        .as_ref()
+        .map(|notification| notification.created.elapsed() > NOTIFICATION_TIMEOUT)
+        .unwrap_or(false);
+    if expired {
+        app.notification = None;
+    }
     Ok(())
 }
 
@@ -79,16 +117,94 @@ B: Backend,
                      Constraint::Length(3),
                      Constraint::Min(1),
                      Constraint::Length(5),
+                     Constraint::Length(1),
                      Constraint::Length(2),
         ].as_ref())
         .split(f.size())
 }
 
-fn draw_cmdline<B>(f: &mut Frame<B>, area: Rect, input: &str)
+fn draw_cmdline<B>(f: &mut Frame<B>, area: Rect, input: &LineEditor, focused: bool)
+    where
+    B: Backend
+{
+    let (before, current, after) = input.split_at_cursor();
+    let mut text = vec![
+        Text::raw(input.prompt.as_str()),
+        Text::raw(before),
+    ];
+    if focused {
+        let current = current.map(String::from).unwrap_or_else(|| String::from(" "));
+        text.push(Text::styled(current, Style::default().modifier(Modifier::REVERSED)));
+    }
+    else if let Some(current) = current {
+        text.push(Text::raw(current.to_string()));
+    }
+    text.push(Text::raw(after));
+
+    Paragraph::new(text.iter())
+        .block(Block::default())
+        .wrap(false)
+        .render(f, area);
+}
+
+fn draw_reverse_search<B>(f: &mut Frame<B>, area: Rect, pattern: &str, found: Option<&str>)
+    where
+    B: Backend
+{
+    let prompt = if found.is_some() || pattern.is_empty() {
+        format!("(reverse-i-search)`{}': ", pattern)
+    }
+    else {
+        format!("(failed reverse-i-search)`{}': ", pattern)
+    };
+    let text = [
+        Text::styled(prompt, Style::default().fg(Color::Yellow)),
+        Text::raw(found.unwrap_or_default()),
+    ];
+    Paragraph::new(text.iter())
+        .block(Block::default())
+        .wrap(false)
+        .render(f, area);
+}
+
+/// Draws the suggestions as a dropdown right above the command line. Items
+/// are padded to the full width since nothing clears what is drawn beneath.
+fn draw_suggestions<B>(f: &mut Frame<B>, cmdline: Rect, suggestions: &Suggestions)
+    where
+    B: Backend
+{
+    let height = (suggestions.items.len() as u16 + 2).min(cmdline.y);
+    let width = cmdline.width.min(60);
+    let area = Rect::new(cmdline.x, cmdline.y - height, width, height);
+    let inner_width = width.saturating_sub(2) as usize;
+    let items: Vec<String> = suggestions.items
+        .iter()
+        .map(|item| format!("{:<width$}", item, width = inner_width))
+        .collect();
+
+    SelectableList::default()
+        .block(Block::default()
+               .title("Suggestions")
+               .border_style(Style::default().fg(Color::White))
+               .borders(Borders::ALL))
+        .items(&items)
+        .select(suggestions.selected)
+        .highlight_style(Style::default()
+                         .fg(Color::Red))
+        .render(f, area);
+}
+
+fn draw_notification<B>(f: &mut Frame<B>, area: Rect, notification: &Notification)
     where
     B: Backend
 {
-    let text = [Text::raw(input)];
+    let color = if notification.is_error {
+        Color::Red
+    }
+    else {
+        Color::Yellow
+    };
+    let text = [Text::styled(&notification.message, Style::default().fg(color))];
     Paragraph::new(text.iter())
         .block(Block::default())
         .wrap(false)
@@ -109,28 +225,18 @@ fn draw_player<B>(f: &mut Frame<B>, area: Rect, app: &mut App)
         ].as_ref())
         .split(area);
 
-    if app.player.audio_changed() {
-        app.current_audio = get_current_media_text(&mut app.audio_queue);
-    }
-    if app.player.video_changed() {
-        app.current_video = get_current_media_text(&mut app.video_queue);
-    }
-
-    app.next_audio = get_next_media_text(&app.audio_queue);
-    app.next_video = get_next_media_text(&app.video_queue);
-
     let audio_text = [
         Text::styled("Current Track: ", Style::default().modifier(Modifier::BOLD)),
-        Text::raw(&app.current_audio),
+        Text::raw(app.audio_queue.current_text()),
         Text::styled("Next Track: ", Style::default().modifier(Modifier::BOLD)),
-        Text::raw(&app.next_audio),
+        Text::raw(app.audio_queue.next_text()),
     ];
 
     let video_text = [
         Text::styled("Current Video: ", Style::default().modifier(Modifier::BOLD)),
-        Text::raw(&app.current_video),
+        Text::raw(app.video_queue.current_text()),
         Text::styled("Next Video: ", Style::default().modifier(Modifier::BOLD)),
-        Text::raw(&app.next_video),
+        Text::raw(app.video_queue.next_text()),
     ];
 
     let player_status_layout = Layout::default()
@@ -140,50 +246,56 @@ fn draw_player<B>(f: &mut Frame<B>, area: Rect, app: &mut App)
                      Constraint::Percentage(50),
         ].as_ref())
         .split(chunks[0]);
+    let progress_layout = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+                     Constraint::Percentage(50),
+                     Constraint::Percentage(50),
+        ].as_ref())
+        .split(chunks[1]);
 
-    let audio_title = format!("Audio Player: {}", state);
+    // The player the transport keys act on has a highlighted border.
+    let (audio_border, video_border) = if app.player.controls_video() {
+        (Style::default(), Style::default().fg(Color::Red))
+    }
+    else {
+        (Style::default().fg(Color::Red), Style::default())
+    };
+
+    let volume = if app.player.is_muted() {
+        String::from("Muted")
+    }
+    else {
+        format!("Vol {}%", app.player.volume())
+    };
+    let audio_title = if app.autoplay {
+        format!("Audio Player: {} | {} | {}x | Autoplay", state, volume, app.player.speed())
+    }
+    else {
+        format!("Audio Player: {} | {} | {}x", state, volume, app.player.speed())
+    };
     Paragraph::new(audio_text.iter())
         .block(
             Block::default()
             .title(&audio_title)
             .borders(Borders::ALL)
+            .border_style(audio_border)
             )
         //.wrap(true)
         .render(f, player_status_layout[0]);
+    let video_title = format!("Video Player: {}", app.player.get_video_status());
     Paragraph::new(video_text.iter())
         .block(
             Block::default()
-            .title("Video Player")
+            .title(&video_title)
             .borders(Borders::ALL)
+            .border_style(video_border)
             )
         //.wrap(true)
         .render(f, player_status_layout[1]);
 
-    draw_progress_bar(f, chunks[1], &app.player);
-}
-
-fn get_current_media_text(queue: &mut VecDeque<(String, String, Option<String>)>) -> String {
-    match queue.pop_front() {
-        Some((title, author, playlist)) => {
-            match playlist {
-                Some(pl) => format!("[{}] {} by {}\n", pl, title, author),
-                None => format!("{} by {}\n", title, author)
-            }
-        },
-        None => String::from("None\n"),
-    }
-}
-
-fn get_next_media_text(queue: &VecDeque<(String, String, Option<String>)>) -> String {
-    match queue.front() {
-        Some((title, author, playlist)) => {
-            match playlist {
-                Some(pl) => format!("[{}] {} by {}\n", pl, title, author),
-                None => format!("{} by {}\n", title, author)
-            }
-        },
-        None => String::from("None\n"),
-    }
+    draw_progress_bar(f, progress_layout[0], &app.player);
+    draw_video_progress_bar(f, progress_layout[1], &app.player);
 }
 
 fn draw_progress_bar<B>(f: &mut Frame<B>, area: Rect, player: &Player) 
@@ -200,6 +312,20 @@ fn draw_progress_bar<B>(f: &mut Frame<B>, area: Rect, player: &Player)
         .render(f, area)
 }
 
+fn draw_video_progress_bar<B>(f: &mut Frame<B>, area: Rect, player: &Player)
+    where
+    B: Backend,
+{
+    let percent = player.get_video_percent_pos();
+    let time = player.get_video_time();
+
+    Gauge::default()
+        .label(&time)
+        .percent(percent)
+        .style(Style::default().fg(Color::Red).bg(Color::Gray))
+        .render(f, area)
+}
+
 fn _draw_selectable_list<B>(f: &mut Frame<B>, 
                            area: Rect, 
                            pane: &Window, 
@@ -227,10 +353,19 @@ fn _draw_selectable_list<B>(f: &mut Frame<B>,
 fn draw_table<B>(f: &mut Frame<B>, 
                  area: Rect, 
                  pane: &Window, 
+                 watched: &HashSet<usize>,
+                 spinner: &str,
                 )
     where
     B: Backend,
 {
+    let title = if pane.loading {
+        format!("{} {}", pane.title, spinner)
+    }
+    else {
+        pane.title.clone()
+    };
+
     let offset = area.height
         .checked_sub(5)
         .and_then(|height| {
@@ -241,16 +376,25 @@ fn draw_table<B>(f: &mut Frame<B>,
         ContentType::MediaContent(content) => content.clone(),
         _ => std::sync::Arc::new(std::sync::RwLock::new(vec![])),
     };
-    let content = content.read().unwrap();
-    let content = content.iter().skip(offset).enumerate().map(|(i, row)| {
-        let color = if i == pane.selected.saturating_sub(offset) {
-            Color::Red
+    let mut content = content.read().unwrap().clone();
+    if pane.loading && content.is_empty() {
+        content.push(vec![format!("{} Loading…", spinner)]);
+    }
+    let content = content.iter().enumerate().skip(offset).map(|(i, row)| {
+        let style = if i == pane.selected {
+            Style::default().fg(Color::Red)
+        }
+        else if pane.highlighted.contains(&i) {
+            Style::default().fg(Color::Yellow).modifier(Modifier::BOLD)
+        }
+        else if watched.contains(&i) {
+            Style::default().fg(Color::DarkGray)
         }
         else {
-            Color::White
+            Style::default().fg(Color::White)
         };
 
-        Row::StyledData(row.iter(), Style::default().fg(color))
+        Row::StyledData(row.iter(), style)
     });
     let headers = match &pane.headers {
         Some(ref headers) => headers.clone(),
@@ -262,7 +406,7 @@ fn draw_table<B>(f: &mut Frame<B>,
         content,
         )
         .block(Block::default()
-               .title(&pane.title)
+               .title(&title)
                .border_style(Style::default().fg(Color::White))
                .borders(Borders::ALL)
               )
@@ -272,14 +416,86 @@ fn draw_table<B>(f: &mut Frame<B>,
         .render(f, area);
 }
 
-fn draw_tabs<B>(f: &mut Frame<B>, area: Rect, tabs: &TabState) 
+fn instance_status(health: &InstanceHealth) -> String {
+    let host = health.url
+        .splitn(2, "://")
+        .last()
+        .unwrap_or(&health.url);
+    let failures = health.recent_failures();
+
+    match health.latency {
+        Some(latency) if failures > 0 => format!("{} ({} ms, {} recent failures)", host, latency.as_millis(), failures),
+        Some(latency) => format!("{} ({} ms)", host, latency.as_millis()),
+        None => host.to_owned(),
+    }
+}
+
+/// Draws scrollable text, `selected` being the first visible line. Links and
+/// timestamps are highlighted.
+fn draw_text<B>(f: &mut Frame<B>, area: Rect, pane: &Window, spinner: &str)
+    where
+    B: Backend,
+{
+    let lines = match &pane.content {
+        ContentType::TextContent(lines) => lines.as_slice(),
+        _ => &[],
+    };
+
+    let mut text = vec![];
+    if pane.loading {
+        text.push(Text::raw(format!("{} Loading…\n", spinner)));
+    }
+    for line in lines.iter().skip(pane.selected) {
+        for (i, word) in line.split(' ').enumerate() {
+            if i > 0 {
+                text.push(Text::raw(" "));
+            }
+            let style = if word.starts_with("http://") || word.starts_with("https://") {
+                Style::default().fg(Color::Cyan).modifier(Modifier::UNDERLINED)
+            }
+            else if is_timestamp(word) {
+                Style::default().fg(Color::Yellow)
+            }
+            else {
+                Style::default()
+            };
+            text.push(Text::styled(word.to_owned(), style));
+        }
+        text.push(Text::raw("\n"));
+    }
+
+    Paragraph::new(text.iter())
+        .block(Block::default()
+               .title(&pane.title)
+               .border_style(Style::default().fg(Color::White))
+               .borders(Borders::ALL))
+        .wrap(true)
+        .render(f, area);
+}
+
+/// Matches `m:ss`, `mm:ss` and `h:mm:ss`, ignoring surrounding punctuation.
+fn is_timestamp(word: &str) -> bool {
+    let word = word.trim_matches(|c: char| !c.is_ascii_digit());
+    let parts: Vec<&str> = word.split(':').collect();
+    if parts.len() < 2 || parts.len() > 3 {
+        return false
+    }
+
+    parts.iter().enumerate().all(|(i, part)| {
+        let len_ok = if i == 0 { !part.is_empty() && part.len() <= 2 } else { part.len() == 2 };
+        len_ok && part.chars().all(|c| c.is_ascii_digit())
+    })
+}
+
+fn draw_tabs<B>(f: &mut Frame<B>, area: Rect, tabs: &TabState, instance: &str) 
     where
     B: Backend,
 {
+    let title = format!("{} | {}", tabs.title, instance);
 
     Tabs::default()
         .block(Block::default()
-               .title(&tabs.title)
+               .title(&title)
                .borders(Borders::ALL))
         .titles(&tabs.items)
         .style(Style::default().fg(Color::White))
@@ -3,18 +3,48 @@ use tui::layout::Constraint;
 pub const VIDEO_HEADERS: [&str; 4] = ["Title", "Author", "Uploaded", "Duration"];
 pub const PLAYLIST_HEADERS: [&str; 3] = ["Name", "Author", "# of Videos"];
 pub const CHANNEL_HEADERS: [&str; 3] = ["Name", "# of Subs", "# of Videos"];
+pub const LOCAL_PLAYLIST_HEADERS: [&str; 3] = ["Name", "# of Videos", "Duration"];
+pub const HISTORY_HEADERS: [&str; 4] = ["Title", "Author", "Watched", "Progress"];
+pub const BOOKMARK_HEADERS: [&str; 3] = ["Title", "Author", "Saved"];
+pub const BOOKMARK_CHANNEL_HEADERS: [&str; 2] = ["Channel", "Saved"];
 pub const PLAYLIST_VIDEO_HEADERS: [&str; 3] = ["Title", "Author", "Duration"];
 pub const QUEUE_HEADERS: [&str; 2] = ["Title", "Author"];
+pub const FORMAT_HEADERS: [&str; 4] = ["Itag", "Type", "Quality", "Codec"];
+pub const SUBSCRIPTION_HEADERS: [&str; 2] = ["Channel", "Channel Id"];
+pub const COMMENT_HEADERS: [&str; 4] = ["Author", "Comment", "Likes", "Posted"];
 pub const VIDEO_COLUMN_CONSTRAINTS: [Constraint; 4] = [
     Constraint::Percentage(60), 
     Constraint::Length(30),
     Constraint::Length(20),
     Constraint::Length(10),
 ];
+pub const HISTORY_COLUMN_CONSTRAINTS: [Constraint; 4] = [
+    Constraint::Percentage(50),
+    Constraint::Length(30),
+    Constraint::Length(20),
+    Constraint::Length(20),
+];
+pub const BOOKMARK_COLUMN_CONSTRAINTS: [Constraint; 3] = [
+    Constraint::Percentage(60),
+    Constraint::Length(30),
+    Constraint::Length(20),
+];
 pub const QUEUE_COLUMN_CONSTRAINTS: [Constraint; 2] = [
     Constraint::Percentage(60),
     Constraint::Percentage(40),
 ];
+pub const FORMAT_COLUMN_CONSTRAINTS: [Constraint; 4] = [
+    Constraint::Length(10),
+    Constraint::Length(20),
+    Constraint::Length(20),
+    Constraint::Percentage(40),
+];
+pub const COMMENT_COLUMN_CONSTRAINTS: [Constraint; 4] = [
+    Constraint::Length(30),
+    Constraint::Percentage(60),
+    Constraint::Length(12),
+    Constraint::Length(15),
+];
 pub const DEFAULT_COLUMN_CONSTRAINTS: [Constraint; 3] = [
     Constraint::Percentage(60), 
     Constraint::Length(30),
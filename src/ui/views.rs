@@ -1,7 +1,10 @@
 use tui::layout::Constraint;
-use std::sync::{
-    Arc,
-    RwLock,
+use std::{
+    collections::HashSet,
+    sync::{
+        Arc,
+        RwLock,
+    },
 };
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
@@ -9,12 +12,17 @@ pub enum ViewType {
     Search,
     Home,
     Queue,
+    Subscriptions,
+    Playlists,
+    History,
+    Bookmarks,
 }
 
 #[derive(Clone, Debug)]
 pub enum ContentType {
     MediaContent(Arc<RwLock<Vec<Vec<String>>>>),
     ListContent(Vec<String>),
+    TextContent(Vec<String>),
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
@@ -30,6 +38,20 @@ pub enum WindowType {
     ChannelPlaylists,
     VideoQueue,
     AudioQueue,
+    VideoDetails,
+    VideoRelated,
+    VideoFormats,
+    VideoComments,
+    SubscriptionFeed,
+    SubscribedChannels,
+    AccountFeed,
+    AccountPlaylists,
+    LocalPlaylists,
+    LocalPlaylistVideos,
+    WatchHistory,
+    BookmarkedVideos,
+    BookmarkedPlaylists,
+    BookmarkedChannels,
 }
 
 #[derive(Clone, Debug)]
@@ -54,6 +76,7 @@ pub struct View {
     pub tabs: TabState,
     pub view_stack: Vec<View>,
     pub root_windows: Vec<Window>,
+    pub media_id: Option<String>,
 }
 
 impl View {
@@ -62,9 +85,30 @@ impl View {
             tabs: TabState::new(tabs, tab_title),
             view_stack: vec![],
             root_windows,
+            media_id: None,
         }
     }
 
+    /// Tags the view with the id of the media it shows so fetched data can
+    /// find its way back to it.
+    pub fn for_media(mut self, media_id: String) -> Self {
+        self.media_id = Some(media_id);
+        self
+    }
+
+    pub fn window_mut(&mut self, window_type: WindowType) -> Option<&mut Window> {
+        self.root_windows
+            .iter_mut()
+            .find(|window| window.window_type == window_type)
+    }
+
+    pub fn find_media_view_mut(&mut self, media_id: &str) -> Option<&mut View> {
+        self.view_stack
+            .iter_mut()
+            .rev()
+            .find(|view| view.media_id.as_deref() == Some(media_id))
+    }
+
     pub fn get_current_view(&self) -> Option<&View> {
         if !self.view_stack.is_empty() {
             self.view_stack.last()
@@ -100,7 +144,9 @@ pub struct Window {
     pub content: ContentType,
     pub headers: Option<Box<[&'static str]>>,
     pub window_type: WindowType,
-    pub column_widths: Box<[Constraint]>
+    pub column_widths: Box<[Constraint]>,
+    pub loading: bool,
+    pub highlighted: HashSet<usize>,
 }
 
 impl Window {
@@ -118,9 +164,39 @@ impl Window {
             content,
             headers,
             window_type,
-            column_widths
+            column_widths,
+            loading: false,
+            highlighted: HashSet::new(),
         }
     }
+
+    pub fn loading(mut self) -> Self {
+        self.loading = true;
+        self
+    }
+
+    pub fn set_content(&mut self, text: Vec<Vec<String>>) {
+        self.content = ContentType::MediaContent(Arc::new(RwLock::new(text)));
+        self.selected = 0;
+        self.loading = false;
+        self.highlighted.clear();
+    }
+
+    /// Replaces the rows of a table while keeping the selection, for content
+    /// that grows or changes in place.
+    pub fn update_content(&mut self, text: Vec<Vec<String>>) {
+        if let ContentType::MediaContent(content) = &self.content {
+            self.selected = self.selected.min(text.len().saturating_sub(1));
+            *content.write().unwrap() = text;
+        }
+        self.loading = false;
+    }
+
+    pub fn set_text(&mut self, lines: Vec<String>) {
+        self.content = ContentType::TextContent(lines);
+        self.selected = 0;
+        self.loading = false;
+    }
 }
 
 impl Default for Window {
@@ -132,6 +208,8 @@ impl Default for Window {
             headers: None,
             window_type: WindowType::SearchVideos,
             column_widths: Box::new([]),
+            loading: false,
+            highlighted: HashSet::new(),
         }
     }
 }
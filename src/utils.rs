@@ -1,138 +1,295 @@
 use crate::{
     app::App,
-    ui::views::{
-        WindowType,
-        Window,
-        ContentType,
-        ViewType,
+    ui::{
+        views::{
+            WindowType,
+            Window,
+            ContentType,
+            ViewType,
+            View,
+        },
+        table_info,
     },
     data::LoadedData,
     media::{
         video::Video,
         channel::Channel,
         playlist::Playlist,
+        comment::{
+            Comments,
+            CommentSort,
+            CommentTarget,
+        },
         Media,
         ListItem,
     },
-    invidious
+    invidious::Invidious,
+    playlists::LocalPlaylist,
+    bookmarks::Bookmarked,
+    queue::QueueEntry,
+    subscriptions::{
+        self,
+        Subscription,
+    },
+    fetch::{
+        Fetcher,
+        Fetched,
+        Page,
+    },
 };
-use failure::Error;
-use tokio::runtime::Runtime;
-use reqwest::Client;
-use std::sync::{
-    Arc,
-    RwLock,
+use std::{
+    collections::HashSet,
+    sync::{
+        Arc,
+        RwLock,
+    },
 };
 
-const SEARCH_URL: &str = "https://www.invidio.us/api/v1/search";
-
 pub fn get_media(window: &Window, data: &LoadedData) -> Option<Box<dyn Media>>{
+    let selected = window.selected;
     match window.window_type {
-        WindowType::SearchVideos => Some(Box::new(data.search_data.videos.0.read().unwrap()[window.selected].clone())),
-        WindowType::PlaylistVideos => Some(Box::new(data.playlist_videos[window.selected].clone())),
-        WindowType::SearchPlaylists => Some(Box::new(data.search_data.playlists.0.read().unwrap()[window.selected].clone())),
-        WindowType::TrendingVideos => Some(Box::new(data.trending_videos[window.selected].clone())),
-        WindowType::PopularVideos => Some(Box::new(data.popular_videos[window.selected].clone())),
-        WindowType::TopVideos => Some(Box::new(data.top_videos[window.selected].clone())),
-        WindowType::SearchChannels => Some(Box::new(data.search_data.channels.0.read().unwrap()[window.selected].clone())),
-        WindowType::ChannelVideos => Some(Box::new(data.channel_videos[window.selected].clone())),
-        WindowType::ChannelPlaylists => Some(Box::new(data.channel_playlists[window.selected].clone())),
+        WindowType::SearchVideos => boxed(data.search_data.videos.0.read().unwrap().get(selected)),
+        WindowType::PlaylistVideos => boxed(data.playlist_videos.get(selected)),
+        WindowType::SearchPlaylists => boxed(data.search_data.playlists.0.read().unwrap().get(selected)),
+        WindowType::TrendingVideos => boxed(data.trending_videos.get(selected)),
+        WindowType::PopularVideos => boxed(data.popular_videos.get(selected)),
+        WindowType::TopVideos => boxed(data.top_videos.get(selected)),
+        WindowType::SearchChannels => boxed(data.search_data.channels.0.read().unwrap().get(selected)),
+        WindowType::ChannelVideos => boxed(data.channel_videos.get(selected)),
+        WindowType::ChannelPlaylists => boxed(data.channel_playlists.get(selected)),
+        WindowType::VideoRelated => boxed(data.related_videos.1.get(selected)),
+        WindowType::SubscriptionFeed => boxed(data.subscription_feed.get(selected)),
+        WindowType::SubscribedChannels => boxed(data.subscriptions.channels.get(selected).map(Subscription::to_channel).as_ref()),
+        WindowType::AccountFeed => boxed(data.account_feed.get(selected)),
+        WindowType::AccountPlaylists => boxed(data.account_playlists.get(selected)),
+        WindowType::LocalPlaylists => boxed(data.playlists.playlists.get(selected)),
+        WindowType::LocalPlaylistVideos => boxed(opened_playlist(data).and_then(|playlist| playlist.videos.get(selected))),
+        WindowType::WatchHistory => boxed(data.watch_history.entries.get(selected).map(|entry| &entry.video)),
+        WindowType::BookmarkedVideos => boxed(data.bookmarks.videos.get(selected).map(|bookmark| &bookmark.item)),
+        WindowType::BookmarkedPlaylists => boxed(data.bookmarks.playlists.get(selected).map(|bookmark| &bookmark.item)),
+        WindowType::BookmarkedChannels => boxed(data.bookmarks.channels.get(selected).map(|bookmark| &bookmark.item)),
         _ => None
     }
 }
 
-pub fn fetch_next_page(client: Client, runtime: Arc<RwLock<Runtime>>, data: &LoadedData, window: &Window) -> Result<(), Error> {
-    //let client = app.client.clone();
+/// The selected video of tables listing videos, for adding it to a local
+/// playlist or recording it in the watch history.
+pub fn get_video(window: &Window, data: &LoadedData) -> Option<Video> {
     match window.window_type {
-        WindowType::SearchVideos => {
-            let query = data.search_data.query.clone();
-            let page = data.search_data.videos.1 + 1;
-            let current_data = data.search_data.videos.0.clone();
-            if let ContentType::MediaContent(ref content) = window.content {
-                let content = content.clone();
-                std::thread::spawn(move || {
-                    runtime.write().unwrap().block_on(
-                        async move {
-                            let page_str = page.to_string();
-                            let params = vec![
-                                ("q", query.as_str()),
-                                ("page", page_str.as_str()),
-                                ("type", "video"),
-                                ("sort_by", "relevance"),
-                            ];
-                            let videos = invidious::invidious_videos(params, &client, SEARCH_URL).await;
-                            match videos {
-                                Ok(mut videos) => {
-                                    current_data.write().unwrap().append(&mut videos);
-                                    *content.write().unwrap() = video_to_text(current_data.read().unwrap().to_vec());
-                                },
-                                Err(e) => log::error!("{}", e),
-                            }
-                        });
-                });
-            }
-        },
-        WindowType::SearchPlaylists => {
-            let query = data.search_data.query.clone();
-            let page = data.search_data.playlists.1 + 1;
-            let current_data = data.search_data.playlists.0.clone();
-            if let ContentType::MediaContent(ref content) = window.content {
-                let content = content.clone();
-                std::thread::spawn(move || {
-                    runtime.write().unwrap().block_on(
-                        async move {
-                            let page_str = page.to_string();
-                            let params = vec![
-                                ("q", query.as_str()),
-                                ("page", page_str.as_str()),
-                                ("type", "playlist"),
-                                ("sort_by", "relevance"),
-                            ];
-                            let playlists = invidious::invidious_playlists(params, &client, SEARCH_URL).await;
-                            match playlists {
-                                Ok(mut playlists) => {
-                                    current_data.write().unwrap().append(&mut playlists);
-                                    *content.write().unwrap() = playlist_to_text(current_data.read().unwrap().to_vec());
-                                },
-                                Err(e) => log::error!("{}", e),
-                            }
-                        });
-                });
-            }
-        },
-        WindowType::SearchChannels => {
-            let query = data.search_data.query.clone();
-            let page = data.search_data.channels.1 + 1;
-            let current_data = data.search_data.channels.0.clone();
-            if let ContentType::MediaContent(ref content) = window.content {
-                let content = content.clone();
-                std::thread::spawn(move || {
-                    runtime.write().unwrap().block_on(
-                        async move {
-                            let page_str = page.to_string();
-                            let params = vec![
-                                ("q", query.as_str()),
-                                ("page", page_str.as_str()),
-                                ("type", "channel"),
-                                ("sort_by", "relevance"),
-                            ];
-                            let channels = invidious::invidious_channels(params, &client, SEARCH_URL).await;
-                            match channels {
-                                Ok(mut channels) => {
-                                    current_data.write().unwrap().append(&mut channels);
-                                    *content.write().unwrap() = channel_to_text(current_data.read().unwrap().to_vec());
-                                },
-                                Err(e) => log::error!("{}", e),
-                            }
-                        });
-                });
-            }
-        },
-        _ => {},
+        WindowType::WatchHistory => return data.watch_history.entries.get(window.selected).map(|entry| entry.video.clone()),
+        WindowType::BookmarkedVideos => return data.bookmarks.videos.get(window.selected).map(|bookmark| bookmark.item.clone()),
+        _ => (),
+    }
+    with_videos(window, data, |videos| videos.get(window.selected).cloned()).and_then(|video| video)
+}
+
+/// Rows of a table listing videos that are in the watch history.
+pub fn watched_rows(window: &Window, data: &LoadedData) -> HashSet<usize> {
+    let watch_history = &data.watch_history;
+    if window.window_type == WindowType::BookmarkedVideos {
+        return data.bookmarks.videos
+            .iter()
+            .enumerate()
+            .filter(|(_, bookmark)| watch_history.is_watched(&bookmark.item.video_id))
+            .map(|(i, _)| i)
+            .collect()
+    }
+    with_videos(window, data, |videos| {
+        videos.iter()
+            .enumerate()
+            .filter(|(_, video)| watch_history.is_watched(&video.video_id))
+            .map(|(i, _)| i)
+            .collect()
+    }).unwrap_or_default()
+}
+
+fn with_videos<T, F>(window: &Window, data: &LoadedData, f: F) -> Option<T>
+where
+    F: FnOnce(&[Video]) -> T,
+{
+    let videos = match window.window_type {
+        WindowType::SearchVideos => return Some(f(&data.search_data.videos.0.read().unwrap())),
+        WindowType::PlaylistVideos => &data.playlist_videos,
+        WindowType::TrendingVideos => &data.trending_videos,
+        WindowType::PopularVideos => &data.popular_videos,
+        WindowType::TopVideos => &data.top_videos,
+        WindowType::ChannelVideos => &data.channel_videos,
+        WindowType::VideoRelated => &data.related_videos.1,
+        WindowType::SubscriptionFeed => &data.subscription_feed,
+        WindowType::AccountFeed => &data.account_feed,
+        WindowType::LocalPlaylistVideos => &opened_playlist(data)?.videos,
+        _ => return None,
+    };
+    Some(f(videos))
+}
+
+pub fn get_local_playlist(window: &Window, data: &LoadedData) -> Option<LocalPlaylist> {
+    match window.window_type {
+        WindowType::LocalPlaylists => data.playlists.playlists.get(window.selected).cloned(),
+        WindowType::LocalPlaylistVideos => opened_playlist(data).cloned(),
+        _ => None
+    }
+}
+
+fn opened_playlist(data: &LoadedData) -> Option<&LocalPlaylist> {
+    data.opened_playlist
+        .as_ref()
+        .and_then(|name| data.playlists.get(name))
+}
+
+fn boxed<T: Media + Clone + 'static>(media: Option<&T>) -> Option<Box<dyn Media>> {
+    media.map(|media| Box::new(media.clone()) as Box<dyn Media>)
+}
+
+pub fn get_playlist(window: &Window, data: &LoadedData) -> Option<Playlist> {
+    match window.window_type {
+        WindowType::SearchPlaylists => data.search_data.playlists.0.read().unwrap().get(window.selected).cloned(),
+        WindowType::ChannelPlaylists => data.channel_playlists.get(window.selected).cloned(),
+        WindowType::AccountPlaylists => data.account_playlists.get(window.selected).cloned(),
+        WindowType::BookmarkedPlaylists => data.bookmarks.playlists.get(window.selected).map(|bookmark| bookmark.item.clone()),
+        _ => None
+    }
+}
+
+pub fn get_channel(window: &Window, data: &LoadedData) -> Option<Channel> {
+    match window.window_type {
+        WindowType::SearchChannels => data.search_data.channels.0.read().unwrap().get(window.selected).cloned(),
+        WindowType::SubscribedChannels => data.subscriptions.channels.get(window.selected).map(Subscription::to_channel),
+        WindowType::BookmarkedChannels => data.bookmarks.channels.get(window.selected).map(|bookmark| bookmark.item.clone()),
+        _ => None
+    }
+}
+
+/// Queue entries for the selection, in the order `Media::play_video` and
+/// `Media::play_audio` load them into mpv. Remote playlists are queued once
+/// their videos are fetched and channels can't be played, so both have none.
+pub fn queue_entries(window: &Window, data: &LoadedData) -> Vec<QueueEntry> {
+    if window.window_type == WindowType::LocalPlaylists {
+        return get_local_playlist(window, data)
+            .map(|playlist| {
+                playlist.videos
+                    .iter()
+                    .map(|video| QueueEntry::new(video, Some(playlist.name.clone())))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+    get_video(window, data)
+        .map(|video| vec![QueueEntry::new(&video, None)])
+        .unwrap_or_default()
+}
+
+/// The selected video, playlist or channel, for bookmarking it.
+pub fn get_bookmarkable(window: &Window, data: &LoadedData) -> Option<Bookmarked> {
+    get_video(window, data).map(Bookmarked::Video)
+        .or_else(|| get_playlist(window, data).map(Bookmarked::Playlist))
+        .or_else(|| get_channel(window, data).map(Bookmarked::Channel))
+}
+
+pub fn fetch_next_page(api: &Invidious, fetcher: &Fetcher, data: &LoadedData, window: &mut Window) {
+    if window.loading {
+        return
+    }
+
+    let (search_type, page) = match window.window_type {
+        WindowType::SearchVideos => ("video", data.search_data.videos.1 + 1),
+        WindowType::SearchPlaylists => ("playlist", data.search_data.playlists.1 + 1),
+        WindowType::SearchChannels => ("channel", data.search_data.channels.1 + 1),
+        _ => return,
+    };
+    let filters = data.search_data.filters.clone();
+    if !filters.includes(search_type) {
+        return
+    }
+
+    let api = api.clone();
+    let query = data.search_data.query.clone();
+    let window_type = window.window_type;
+    window.loading = true;
+    fetcher.spawn(async move {
+        let result = match window_type {
+            WindowType::SearchVideos => api.search_page(&query, &filters, search_type, page).await.map(Page::Videos),
+            WindowType::SearchPlaylists => api.search_page(&query, &filters, search_type, page).await.map(Page::Playlists),
+            _ => api.search_page(&query, &filters, search_type, page).await.map(Page::Channels),
+        };
+        Fetched::NextPage {
+            window_type,
+            query,
+            filters,
+            page,
+            result,
+        }
+    });
+}
+
+pub fn fetch_comments(api: &Invidious, fetcher: &Fetcher, video_id: String, sort: CommentSort, target: CommentTarget) {
+    let api = api.clone();
+    fetcher.spawn(async move {
+        let result = api.comments(&video_id, sort, target.continuation()).await;
+        Fetched::Comments {
+            video_id,
+            sort,
+            target,
+            result,
+        }
+    });
+}
+
+/// Related videos and formats belong to the most recently opened video, so
+/// when an older video view becomes current again, e.g. by going back, they
+/// are emptied and fetched again before their rows can be picked.
+pub fn reload_video_view(app: &mut App) {
+    let view = match app.view_list.get_mut(&app.focused_view).and_then(|root_view| root_view.get_current_view_mut()) {
+        Some(view) => view,
+        None => return,
+    };
+    let video_id = match view.media_id.clone() {
+        Some(video_id) => video_id,
+        None => return,
+    };
+    match view.window_mut(WindowType::VideoRelated) {
+        // Still loading from when the view was opened.
+        Some(window) if !window.loading => (),
+        _ => return,
+    }
+    if app.loaded_data.related_videos.0 == video_id {
+        return
+    }
+
+    for window_type in &[WindowType::VideoRelated, WindowType::VideoFormats] {
+        if let Some(window) = view.window_mut(*window_type) {
+            window.set_content(vec![]);
+            window.loading = true;
+        }
+    }
+    app.loaded_data.related_videos = (video_id.clone(), vec![]);
+    app.loaded_data.video_formats.clear();
+    app.loaded_data.formats_video = None;
+
+    let api = app.api.clone();
+    app.fetcher.spawn(async move {
+        let result = api.video(&video_id).await;
+        Fetched::Video(video_id, result)
+    });
+}
+
+/// Comments counterpart of `fetch_next_page`. The loaded comments belong to
+/// the most recently opened video, so going back to an older one reloads them.
+pub fn fetch_next_comments(api: &Invidious, fetcher: &Fetcher, comments: &Comments, video_id: &str, window: &mut Window) {
+    if window.loading {
+        return
+    }
+
+    if comments.video_id != video_id {
+        window.loading = true;
+        fetch_comments(api, fetcher, video_id.to_owned(), CommentSort::Top, CommentTarget::Reload);
+    }
+    else if let Some(continuation) = &comments.continuation {
+        window.loading = true;
+        fetch_comments(api, fetcher, video_id.to_owned(), comments.sort, CommentTarget::NextPage(continuation.clone()));
     }
-    Ok(())
 }
-    
+
 pub fn next_selection(mut window: &mut Window, len: usize) {
     if window.selected + 1 < len {
         window.selected+=1;
@@ -145,12 +302,53 @@ pub fn prev_selection(mut window: &mut Window) {
     }
 }
 
+pub fn format_seconds(seconds: i64) -> String {
+    let hours = seconds / 3600;
+    let seconds = seconds % 3600;
+    let minutes = seconds / 60;
+    let seconds = seconds % 60;
+    format!("{:0>2}:{:0>2}:{:0>2}", hours, minutes, seconds)
+}
+
+/// Formats a count with thousands separators, e.g. 1234567 -> 1,234,567.
+pub fn format_count(count: u64) -> String {
+    let digits = count.to_string();
+    let groups: Vec<&str> = digits.as_bytes()
+        .rchunks(3)
+        .rev()
+        .map(|group| std::str::from_utf8(group).unwrap_or_default())
+        .collect();
+    groups.join(",")
+}
+
 pub fn video_to_text(videos: Vec<Video>) -> Vec<Vec<String>> {
     videos.into_iter().map(|item| {
         item.into_text()
     }).collect()
 }
 
+/// Recommended videos come without an upload date, so the column is left
+/// blank to keep them aligned with `VIDEO_HEADERS`.
+pub fn related_to_text(videos: Vec<Video>) -> Vec<Vec<String>> {
+    videos.into_iter().map(|item| {
+        let mut row = item.into_text();
+        if row.len() == 3 {
+            row.insert(2, String::new());
+        }
+        row
+    }).collect()
+}
+
+/// Extracts the video id from a watch url as generated by `Invidious::watch_url`,
+/// or from a stream url of an instance proxy.
+pub fn video_id_from_url(url: &str) -> Option<String> {
+    let key = if url.contains("/latest_version?") { "id=" } else { "v=" };
+    url.split(&['?', '&'][..])
+        .find(|param| param.starts_with(key))
+        .map(|param| param[key.len()..].to_owned())
+        .filter(|id| !id.is_empty())
+}
+
 pub fn playlist_to_text(playlists: Vec<Playlist>) -> Vec<Vec<String>> {
     playlists.into_iter().map(|item| {
         item.into_text()        
@@ -163,37 +361,185 @@ pub fn channel_to_text(channels: Vec<Channel>) -> Vec<Vec<String>> {
     }).collect()
 }
 
-pub fn update_queue_view(app: &mut App) {
-    let audio_queue = app.audio_queue
-        .iter()
-        .map(|(title, author, playlist)| {
-            let title = match playlist {
-                Some(pl) => format!("[{}] {}", pl, title),
-                None => title.to_string(),
-            };
-            vec![title, author.to_string()]
-        })
-    .collect();
-    let video_queue = app.video_queue
-        .iter()
-        .map(|(title, author, playlist)| {
-            let title = match playlist {
-                Some(pl) => format!("[{}] {}", pl, title),
-                None => title.to_string(),
-            };
-            vec![title, author.to_string()]
-        })
-    .collect();
+/// Fetches the latest videos of every subscribed channel into the feed.
+pub fn load_subscription_feed(app: &mut App) {
+    let channels = app.loaded_data.subscriptions.channels.clone();
+    if let Some(view) = app.view_list.get_mut(&ViewType::Subscriptions) {
+        if let Some(window) = view.window_mut(WindowType::SubscriptionFeed) {
+            if window.loading {
+                return
+            }
+            // The videos of the last channel may still be shown.
+            if channels.is_empty() {
+                window.set_content(vec![]);
+            }
+            else {
+                window.loading = true;
+            }
+        }
+    }
+    if channels.is_empty() {
+        app.loaded_data.subscription_feed.clear();
+        return
+    }
 
-    if let Some(view) = app.view_list.get_mut(&ViewType::Queue) {
-        if let Some(window) = view.root_windows.get_mut(0) {
-            if let ContentType::MediaContent(ref queue_list) = window.content {
-                *queue_list.write().unwrap() = audio_queue;
+    let api = app.api.clone();
+    app.fetcher.spawn(async move {
+        let (videos, failed) = subscriptions::fetch_feed(api, channels).await;
+        Fetched::SubscriptionFeed(videos, failed)
+    });
+}
+
+pub fn update_subscriptions_view(app: &mut App) {
+    let text = app.loaded_data.subscriptions.to_text();
+    if let Some(view) = app.view_list.get_mut(&ViewType::Subscriptions) {
+        if let Some(window) = view.window_mut(WindowType::SubscribedChannels) {
+            window.update_content(text);
+        }
+    }
+}
+
+/// Adds the Feed and My Playlists tabs to the home view when logged in and
+/// removes them otherwise.
+pub fn update_account_tabs(app: &mut App) {
+    let logged_in = app.api.is_logged_in();
+    if let Some(view) = app.view_list.get_mut(&ViewType::Home) {
+        let is_account_window = |window: &Window| {
+            window.window_type == WindowType::AccountFeed || window.window_type == WindowType::AccountPlaylists
+        };
+        let has_tabs = view.root_windows.iter().any(is_account_window);
+        if logged_in && !has_tabs {
+            view.tabs.items.push("Feed".to_owned());
+            view.tabs.items.push("My Playlists".to_owned());
+            view.root_windows.push(Window::new("Feed".to_owned(), 0, ContentType::MediaContent(Arc::new(RwLock::new(vec![]))), Some(Box::new(table_info::VIDEO_HEADERS)), WindowType::AccountFeed, Box::new(table_info::VIDEO_COLUMN_CONSTRAINTS)));
+            view.root_windows.push(Window::new("My Playlists".to_owned(), 0, ContentType::MediaContent(Arc::new(RwLock::new(vec![]))), Some(Box::new(table_info::PLAYLIST_HEADERS)), WindowType::AccountPlaylists, Box::new(table_info::DEFAULT_COLUMN_CONSTRAINTS)));
+        }
+        else if !logged_in && has_tabs {
+            let mut i = 0;
+            while i < view.root_windows.len() {
+                if is_account_window(&view.root_windows[i]) {
+                    view.root_windows.remove(i);
+                    view.tabs.items.remove(i);
+                }
+                else {
+                    i += 1;
+                }
+            }
+            view.tabs.selected = view.tabs.selected.min(view.root_windows.len().saturating_sub(1));
+        }
+    }
+}
+
+/// Fetches the feed, playlists and subscriptions of the logged in account.
+pub fn load_account(app: &mut App) {
+    if !app.api.is_logged_in() {
+        return
+    }
+    if let Some(view) = app.view_list.get_mut(&ViewType::Home) {
+        for window_type in &[WindowType::AccountFeed, WindowType::AccountPlaylists] {
+            if let Some(window) = view.window_mut(*window_type) {
+                window.loading = true;
             }
         }
-        if let Some(window) = view.root_windows.get_mut(1) {
-            if let ContentType::MediaContent(ref queue_list) = window.content {
-                *queue_list.write().unwrap() = video_queue;
+    }
+
+    let api = app.api.clone();
+    app.fetcher.spawn(async move {
+        Fetched::Account(api.load_account().await)
+    });
+}
+
+/// The window shown in the focused view, following opened media.
+pub fn focused_window(app: &App) -> Option<&Window> {
+    let root_view = app.view_list.get(&app.focused_view)?;
+    let view = root_view.get_current_view()?;
+    view.root_windows.get(view.tabs.selected)
+}
+
+/// Opens the selected local playlist, listing its videos for editing.
+pub fn open_local_playlist(app: &mut App) {
+    let playlist = app.view_list
+        .get(&ViewType::Playlists)
+        .and_then(|view| view.root_windows.get(view.tabs.selected))
+        .and_then(|window| get_local_playlist(window, &app.loaded_data));
+    if let Some(playlist) = playlist {
+        let window = Window::new(
+            playlist.name.clone(),
+            0,
+            ContentType::MediaContent(Arc::new(RwLock::new(related_to_text(playlist.videos.clone())))),
+            Some(Box::new(table_info::VIDEO_HEADERS)),
+            WindowType::LocalPlaylistVideos,
+            Box::new(table_info::VIDEO_COLUMN_CONSTRAINTS),
+            );
+        let view = View::new(vec![window], vec!["Videos".to_owned()], playlist.name.clone())
+            .for_media(playlist.name.clone());
+        if let Some(root_view) = app.view_list.get_mut(&ViewType::Playlists) {
+            root_view.view_stack = vec![view];
+        }
+        app.loaded_data.opened_playlist = Some(playlist.name);
+    }
+}
+
+/// Refreshes the playlist list and the opened playlist after an edit.
+pub fn update_playlists_view(app: &mut App) {
+    let text = app.loaded_data.playlists.to_text();
+    let opened = opened_playlist(&app.loaded_data).cloned();
+    if let Some(root_view) = app.view_list.get_mut(&ViewType::Playlists) {
+        if let Some(window) = root_view.window_mut(WindowType::LocalPlaylists) {
+            window.update_content(text);
+        }
+        match opened {
+            Some(playlist) => {
+                for view in root_view.view_stack.iter_mut() {
+                    if let Some(window) = view.window_mut(WindowType::LocalPlaylistVideos) {
+                        window.title = playlist.name.clone();
+                        window.update_content(related_to_text(playlist.videos.clone()));
+                        view.tabs.title = playlist.name.clone();
+                        view.media_id = Some(playlist.name.clone());
+                    }
+                }
+            },
+            None => root_view.view_stack.clear(),
+        }
+    }
+}
+
+pub fn update_history_view(app: &mut App) {
+    let text = app.loaded_data.watch_history.to_text();
+    if let Some(view) = app.view_list.get_mut(&ViewType::History) {
+        if let Some(window) = view.window_mut(WindowType::WatchHistory) {
+            window.update_content(text);
+        }
+    }
+}
+
+pub fn update_bookmarks_view(app: &mut App) {
+    let bookmarks = &app.loaded_data.bookmarks;
+    let tables = vec![
+        (WindowType::BookmarkedVideos, bookmarks.videos_to_text()),
+        (WindowType::BookmarkedPlaylists, bookmarks.playlists_to_text()),
+        (WindowType::BookmarkedChannels, bookmarks.channels_to_text()),
+    ];
+    if let Some(view) = app.view_list.get_mut(&ViewType::Bookmarks) {
+        for (window_type, text) in tables {
+            if let Some(window) = view.window_mut(window_type) {
+                window.update_content(text);
+            }
+        }
+    }
+}
+
+/// Mirrors both queues into the queue view, highlighting the playing entries.
+pub fn update_queue_view(app: &mut App) {
+    let queues = vec![
+        (WindowType::AudioQueue, app.audio_queue.to_text(), app.audio_queue.current),
+        (WindowType::VideoQueue, app.video_queue.to_text(), app.video_queue.current),
+    ];
+    if let Some(view) = app.view_list.get_mut(&ViewType::Queue) {
+        for (window_type, text, current) in queues {
+            if let Some(window) = view.window_mut(window_type) {
+                window.update_content(text);
+                window.highlighted = current.into_iter().collect();
             }
         }
     }
@@ -0,0 +1,165 @@
+use serde::{
+    Serialize,
+    Deserialize,
+};
+use failure::Error;
+use chrono::{
+    Local,
+    TimeZone,
+};
+use crate::{
+    store,
+    media::video::Video,
+    utils,
+};
+use std::time::{
+    Duration,
+    Instant,
+};
+
+const WATCH_HISTORY_FILE: &str = "watch_history.json";
+const MAX_ENTRIES: usize = 1000;
+/// Positions closer than this to either end of a video start it over.
+const RESUME_MARGIN: i64 = 10;
+/// How often positions are saved while playing.
+const SAVE_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchEntry {
+    pub video: Video,
+    pub position: i64,
+    pub watched_at: i64,
+}
+
+impl WatchEntry {
+    pub fn to_text(&self) -> Vec<String> {
+        let watched = Local.timestamp_opt(self.watched_at, 0)
+            .single()
+            .map(|date| date.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_default();
+        let progress = if self.video.length_seconds > 0 {
+            format!("{} / {}", utils::format_seconds(self.position), utils::format_seconds(self.video.length_seconds))
+        }
+        else {
+            utils::format_seconds(self.position)
+        };
+        vec![self.video.title.clone(), self.video.author.clone(), watched, progress]
+    }
+}
+
+/// Started videos, newest first, with how far they were played.
+#[derive(Debug)]
+pub struct WatchHistory {
+    pub entries: Vec<WatchEntry>,
+    pub recording: bool,
+    changed: bool,
+    saved: Instant,
+}
+
+impl WatchHistory {
+    pub fn load(recording: bool) -> Self {
+        WatchHistory {
+            entries: store::load(WATCH_HISTORY_FILE),
+            recording,
+            changed: false,
+            saved: Instant::now(),
+        }
+    }
+
+    pub fn save(&mut self) -> Result<(), Error> {
+        self.changed = false;
+        self.saved = Instant::now();
+        store::save(WATCH_HISTORY_FILE, &self.entries)
+    }
+
+    /// Whether there are changes that have waited long enough to be saved.
+    pub fn save_due(&self) -> bool {
+        self.changed && self.saved.elapsed() >= SAVE_INTERVAL
+    }
+
+    /// Saves only if something was recorded since the last save.
+    pub fn save_changes(&mut self) -> Result<(), Error> {
+        if self.changed {
+            return self.save()
+        }
+        Ok(())
+    }
+
+    /// Moves the video to the front of the history, keeping its position.
+    /// Details it was recorded with are kept when `video` comes from a queue
+    /// entry, which knows less about it.
+    pub fn record(&mut self, video: &Video) {
+        if !self.recording {
+            return
+        }
+        let (video, position) = match self.entries.iter().position(|entry| entry.video.video_id == video.video_id) {
+            Some(index) => {
+                let entry = self.entries.remove(index);
+                if video.published_text.is_empty() {
+                    (entry.video, entry.position)
+                }
+                else {
+                    (video.clone(), entry.position)
+                }
+            },
+            None => (video.clone(), 0),
+        };
+        self.entries.insert(0, WatchEntry {
+            video,
+            position,
+            watched_at: chrono::Utc::now().timestamp(),
+        });
+        self.entries.truncate(MAX_ENTRIES);
+        self.changed = true;
+    }
+
+    pub fn update_position(&mut self, video_id: &str, position: i64) {
+        if !self.recording {
+            return
+        }
+        if let Some(entry) = self.entries.iter_mut().find(|entry| entry.video.video_id == video_id) {
+            if entry.position != position {
+                entry.position = position;
+                self.changed = true;
+            }
+        }
+    }
+
+    /// Where to resume a partially watched video.
+    pub fn resume_position(&self, video_id: &str) -> Option<i64> {
+        let entry = self.entries.iter().find(|entry| entry.video.video_id == video_id)?;
+        let length = entry.video.length_seconds;
+        if entry.position < RESUME_MARGIN || (length > 0 && entry.position > length - RESUME_MARGIN) {
+            return None
+        }
+        Some(entry.position)
+    }
+
+    pub fn is_watched(&self, video_id: &str) -> bool {
+        self.entries.iter().any(|entry| entry.video.video_id == video_id)
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.changed = true;
+    }
+
+    pub fn to_text(&self) -> Vec<Vec<String>> {
+        self.entries
+            .iter()
+            .map(WatchEntry::to_text)
+            .collect()
+    }
+}
+
+impl Default for WatchHistory {
+    fn default() -> Self {
+        WatchHistory {
+            entries: vec![],
+            recording: true,
+            changed: false,
+            saved: Instant::now(),
+        }
+    }
+}